@@ -0,0 +1,202 @@
+use crate::{MAX_BASE, PartitionsParts, digit_char};
+
+/// A position of a number whose digit is not the number of times the
+/// position's index occurs as a digit
+#[derive(Debug, Eq, PartialEq)]
+pub struct WrongPosition {
+    /// Index of the position, and the digit it counts
+    pub idx: usize,
+    pub count: u8,
+    /// Number of times the digit `idx` occurs in the number
+    pub actual_count: usize,
+}
+
+impl std::fmt::Display for WrongPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.idx >= MAX_BASE {
+            return write!(
+                f,
+                "position {} ({}): {} is not a digit, so occurs 0 times, not \
+                 {}",
+                self.idx,
+                digit_char(self.count),
+                self.idx,
+                self.count,
+            );
+        }
+        write!(
+            f,
+            "position {} ({}): {} occurs {} times, not {}",
+            self.idx,
+            digit_char(self.count),
+            digit_char(self.idx as u8),
+            self.actual_count,
+            self.count,
+        )
+    }
+}
+
+/// Positions of an autobiographical number whose digit is wrong, where the
+/// digit at each position `idx` should be the number of times `idx` occurs.
+/// A digit that is not the index of any position also makes some position
+/// wrong, since the digits would then sum to more than the number of
+/// digits.
+/// Positions whose index is not a digit in any base are counted like any
+/// other position, and their index never occurs.
+pub fn verify_autobiographical(digits: &[u8]) -> Vec<WrongPosition> {
+    let mut counts = vec![0usize; digits.len().max(MAX_BASE)];
+    for digit in digits {
+        counts[*digit as usize] += 1;
+    }
+    digits
+        .iter()
+        .enumerate()
+        .filter(|(idx, count)| **count as usize != counts[*idx])
+        .map(|(idx, count)| WrongPosition {
+            idx,
+            count: *count,
+            actual_count: counts[idx],
+        })
+        .collect()
+}
+
+/// Iterate over the autobiographical numbers with `n_digits` digits in base
+/// `base`, such as `6210001000`, where the digit at each position `idx` is
+/// the number of times `idx` occurs.
+/// Every digit counts a position, so the digits sum to `n_digits`, and
+/// their nonzero values are a partition of `n_digits`. Each partition
+/// determines the only number that its digits could describe, which is
+/// autobiographical if its own digits are the same partition.
+/// Numbers are yielded by their number of nonzero digits, and then in the
+/// order of their partitions.
+pub fn solve_autobiographical(
+    n_digits: usize,
+    base: u8,
+) -> impl Iterator<Item = Vec<u8>> {
+    let n_digits = if n_digits <= base as usize {
+        n_digits
+    } else {
+        // Positions could not be written as digits
+        0
+    };
+    let max_part = (base as usize - 1).min(n_digits) as u8;
+    (1..=n_digits)
+        .flat_map(move |n_nonzero| {
+            PartitionsParts::new(n_nonzero, n_digits, max_part)
+        })
+        .filter_map(move |parts| {
+            let mut counts = [0u8; MAX_BASE];
+            counts[0] = (n_digits - parts.len()) as u8;
+            for part in &parts {
+                counts[*part as usize] += 1;
+            }
+            let digits = &counts[..n_digits];
+            let mut nonzero: Vec<u8> =
+                digits.iter().copied().filter(|count| *count != 0).collect();
+            nonzero.sort();
+            (nonzero == parts).then(|| digits.to_vec())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{digits_string, verify::parse_digits};
+
+    use super::{solve_autobiographical, verify_autobiographical};
+
+    #[test]
+    fn test_solve_autobiographical() {
+        let numbers: Vec<String> = (1..=10)
+            .flat_map(|n_digits| solve_autobiographical(n_digits, 10))
+            .map(|digits| digits_string(&digits))
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                "2020",
+                "1210",
+                "21200",
+                "3211000",
+                "42101000",
+                "521001000",
+                "6210001000"
+            ]
+        );
+        assert_eq!(solve_autobiographical(11, 10).count(), 0);
+        let numbers: Vec<String> = solve_autobiographical(11, 12)
+            .map(|digits| digits_string(&digits))
+            .collect();
+        assert_eq!(numbers, ["72100001000"]);
+    }
+
+    #[test]
+    fn test_solve_autobiographical_brute_force() {
+        for base in 2..=6u8 {
+            for n_digits in 1..=base as usize {
+                let mut expected = BTreeSet::new();
+                let mut digits = vec![0u8; n_digits];
+                for start in 0..(base as usize).pow(n_digits as u32) {
+                    let mut n = start;
+                    for digit in digits.iter_mut() {
+                        *digit = (n % base as usize) as u8;
+                        n /= base as usize;
+                    }
+                    if verify_autobiographical(&digits).is_empty() {
+                        expected.insert(digits.clone());
+                    }
+                }
+                let actual: Vec<Vec<u8>> =
+                    solve_autobiographical(n_digits, base).collect();
+                assert_eq!(actual.len(), expected.len());
+                assert_eq!(
+                    actual.into_iter().collect::<BTreeSet<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_autobiographical() {
+        let digits = parse_digits("6210001000", 10).unwrap();
+        assert!(verify_autobiographical(&digits).is_empty());
+        let digits = parse_digits("6210001001", 10).unwrap();
+        let wrong: Vec<String> = verify_autobiographical(&digits)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            wrong,
+            [
+                "position 0 (6): 0 occurs 5 times, not 6",
+                "position 1 (2): 1 occurs 3 times, not 2",
+                "position 9 (1): 9 occurs 0 times, not 1"
+            ]
+        );
+        // Digits that are not positions
+        let digits = parse_digits("1217", 10).unwrap();
+        assert!(!verify_autobiographical(&digits).is_empty());
+        // Positions whose index is not a digit
+        let mut digits = parse_digits("z", 36).unwrap();
+        digits.extend(std::iter::repeat_n(0, 39));
+        let wrong: Vec<String> = verify_autobiographical(&digits)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            wrong,
+            [
+                "position 0 (Z): 0 occurs 39 times, not 35",
+                "position 35 (0): Z occurs 1 times, not 0"
+            ]
+        );
+        digits[39] = 1;
+        let wrong = verify_autobiographical(&digits);
+        assert_eq!(
+            wrong.last().unwrap().to_string(),
+            "position 39 (1): 39 is not a digit, so occurs 0 times, not 1"
+        );
+    }
+}
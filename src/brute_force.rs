@@ -0,0 +1,225 @@
+//! Exhaustive search for self-descriptive numbers, for use as an oracle when
+//! testing the pruned search.
+//! This is only practical for small pair counts.
+
+use std::collections::BTreeSet;
+
+use crate::{Counts, MAX_BASE};
+
+/// Enumerate the number of repetitions of the pair describing each digit,
+/// given the number of times each digit occurs.
+/// `reps[digit]` is set for every digit before `digit`.
+fn enumerate_reps(
+    base: u8,
+    written: Counts,
+    counts: &[u8],
+    digit: usize,
+    reps_remaining: usize,
+    reps: &mut [u8; MAX_BASE],
+    res: &mut BTreeSet<Vec<u8>>,
+) {
+    if digit == counts.len() {
+        if reps_remaining == 0 {
+            check(base, written, counts, reps, res);
+        }
+        return;
+    }
+    if counts[digit] == 0 {
+        reps[digit] = 0;
+        enumerate_reps(
+            base,
+            written,
+            counts,
+            digit + 1,
+            reps_remaining,
+            reps,
+            res,
+        );
+        return;
+    }
+    for digit_reps in 1..=counts[digit].min(reps_remaining as u8) {
+        reps[digit] = digit_reps;
+        enumerate_reps(
+            base,
+            written,
+            counts,
+            digit + 1,
+            reps_remaining - digit_reps as usize,
+            reps,
+            res,
+        );
+    }
+}
+
+/// Build the number in which each digit that occurs is described by
+/// `reps[digit]` copies of the pair `(counts[digit], digit)`, with counts
+/// written as `written` in base `base`, and keep it if the digit counts are
+/// correct.
+fn check(
+    base: u8,
+    written: Counts,
+    counts: &[u8],
+    reps: &[u8; MAX_BASE],
+    res: &mut BTreeSet<Vec<u8>>,
+) {
+    let mut pairs: Vec<(u8, u8)> = (0..counts.len() as u8)
+        .filter(|digit| counts[*digit as usize] != 0)
+        .map(|digit| (counts[digit as usize], digit))
+        .collect();
+    pairs.sort_by(|lhs, rhs| rhs.cmp(lhs));
+    let mut digits = Vec::new();
+    for (count, digit) in pairs {
+        for _ in 0..reps[digit as usize] {
+            digits.extend(written.digits(count, base));
+            digits.push(digit);
+        }
+    }
+    let mut actual_counts = vec![0usize; counts.len()];
+    for digit in &digits {
+        actual_counts[*digit as usize] += 1;
+    }
+    if actual_counts
+        .iter()
+        .zip(counts)
+        .all(|(actual, count)| *actual == *count as usize)
+    {
+        res.insert(digits);
+    }
+}
+
+/// Enumerate the number of times each digit occurs, where no digit occurs
+/// more than `max_count` times.
+/// `counts[digit]` is set for every digit before `digit`.
+fn enumerate_counts(
+    n_pairs: usize,
+    written: Counts,
+    max_count: u8,
+    digit: usize,
+    digits_remaining: usize,
+    counts: &mut Vec<u8>,
+    res: &mut BTreeSet<Vec<u8>>,
+) {
+    let base = counts.len();
+    if digit == base {
+        if digits_remaining == 0 {
+            enumerate_reps(
+                base as u8,
+                written,
+                counts,
+                0,
+                n_pairs,
+                &mut [0; MAX_BASE],
+                res,
+            );
+        }
+        return;
+    }
+    for count in 0..=(max_count as usize).min(digits_remaining) {
+        counts[digit] = count as u8;
+        enumerate_counts(
+            n_pairs,
+            written,
+            max_count,
+            digit + 1,
+            digits_remaining - count,
+            counts,
+            res,
+        );
+    }
+}
+
+/// All self-descriptive numbers in base `base` with `n_pairs` pairs, with
+/// counts written as `counts`.
+/// Each number is a sequence of digits, with pairs ordered by count and
+/// then by digit, both in descending order, matching
+/// [`crate::Solution::expand`].
+pub fn brute_force(
+    n_pairs: usize,
+    base: u8,
+    counts: Counts,
+) -> BTreeSet<Vec<u8>> {
+    let mut res = BTreeSet::new();
+    let max_count = counts.max_count(n_pairs, base);
+    for n_digits in counts.n_digits_range(n_pairs, base) {
+        let mut digit_counts = vec![0; base as usize];
+        enumerate_counts(
+            n_pairs,
+            counts,
+            max_count,
+            0,
+            n_digits,
+            &mut digit_counts,
+            &mut res,
+        );
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{Convention, Counts, digits_string, solve_iter, verify_digits};
+
+    use super::brute_force;
+
+    /// Compare the expanded output of the pruned search with the brute
+    /// force search
+    fn check_solve(n_pairs: usize, base: u8) {
+        let expected = brute_force(n_pairs, base, Counts::SingleDigit);
+        for digits in &expected {
+            assert!(
+                verify_digits(digits, Convention::CountDigit)
+                    .unwrap()
+                    .is_self_descriptive()
+            );
+        }
+        let expanded: Vec<Vec<u8>> = solve_iter(n_pairs, base, ..)
+            .flat_map(|solution| solution.expand())
+            .collect();
+        let actual: BTreeSet<Vec<u8>> = expanded.iter().cloned().collect();
+        assert_eq!(
+            actual.len(),
+            expanded.len(),
+            "duplicate numbers for {n_pairs} pairs in base {base}"
+        );
+        let missing: Vec<String> = expected
+            .difference(&actual)
+            .map(|digits| digits_string(digits))
+            .collect();
+        let unexpected: Vec<String> = actual
+            .difference(&expected)
+            .map(|digits| digits_string(digits))
+            .collect();
+        assert!(
+            missing.is_empty() && unexpected.is_empty(),
+            "{n_pairs} pairs in base {base}: missing {missing:?}, \
+             unexpected {unexpected:?}"
+        );
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let brute_force =
+            |n_pairs| brute_force(n_pairs, 10, Counts::SingleDigit);
+        assert_eq!(brute_force(1), BTreeSet::from([vec![2, 2]]));
+        assert_eq!(brute_force(2), BTreeSet::from([vec![4, 4, 4, 4]]));
+        assert!(brute_force(5).contains(&vec![4, 4, 4, 2, 4, 2, 2, 9, 2, 9]));
+    }
+
+    #[test]
+    fn test_solve_base_10() {
+        for n_pairs in 1..=6 {
+            check_solve(n_pairs, 10);
+        }
+    }
+
+    #[test]
+    fn test_solve_small_bases() {
+        for base in 2..=6 {
+            for n_pairs in 1..=7 {
+                check_solve(n_pairs, base);
+            }
+        }
+    }
+}
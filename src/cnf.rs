@@ -0,0 +1,314 @@
+//! Self-descriptive numbers as a boolean satisfiability problem in
+//! conjunctive normal form, for external SAT solvers, and a small DPLL
+//! solver for checking the encoding.
+
+/// A literal: a positive variable index for the variable, and its negation
+/// for the negated variable, as in the DIMACS format. Variables are
+/// numbered from 1.
+pub type Lit = i32;
+
+/// A formula in conjunctive normal form
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cnf {
+    pub n_vars: usize,
+    /// Clauses, each satisfied if any of its literals is true
+    pub clauses: Vec<Vec<Lit>>,
+    /// Comment lines written before the header
+    pub comments: Vec<String>,
+}
+
+impl Cnf {
+    pub fn new_var(&mut self) -> Lit {
+        self.n_vars += 1;
+        self.n_vars as Lit
+    }
+
+    pub fn add_clause(&mut self, clause: impl IntoIterator<Item = Lit>) {
+        self.clauses.push(clause.into_iter().collect());
+    }
+
+    /// Iterate over every model of the formula, as the value of each
+    /// variable by its index minus 1.
+    /// Variables that are still unassigned once every clause is satisfied
+    /// are false.
+    pub fn models(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        let mut stack = vec![vec![None; self.n_vars]];
+        std::iter::from_fn(move || {
+            while let Some(mut assignment) = stack.pop() {
+                if !self.propagate(&mut assignment) {
+                    continue;
+                }
+                // Branch on a variable of the first unsatisfied clause
+                let unassigned = self.clauses.iter().find_map(|clause| {
+                    let mut unassigned = None;
+                    for lit in clause {
+                        match value(&assignment, *lit) {
+                            Some(true) => return None,
+                            Some(false) => {}
+                            None => unassigned = Some(*lit),
+                        }
+                    }
+                    unassigned
+                });
+                let Some(lit) = unassigned else {
+                    return Some(
+                        assignment
+                            .into_iter()
+                            .map(|value| value.unwrap_or(false))
+                            .collect(),
+                    );
+                };
+                let var = lit.unsigned_abs() as usize - 1;
+                let mut negated = assignment.clone();
+                negated[var] = Some(lit < 0);
+                assignment[var] = Some(lit > 0);
+                stack.push(negated);
+                stack.push(assignment);
+            }
+            None
+        })
+    }
+
+    /// Assign the literals of unit clauses until none are left, returning
+    /// false if a clause is falsified
+    fn propagate(&self, assignment: &mut [Option<bool>]) -> bool {
+        loop {
+            let mut changed = false;
+            for clause in &self.clauses {
+                let mut n_unassigned = 0;
+                let mut unit = 0;
+                let mut satisfied = false;
+                for lit in clause {
+                    match value(assignment, *lit) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            n_unassigned += 1;
+                            unit = *lit;
+                        }
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match n_unassigned {
+                    0 => return false,
+                    1 => {
+                        assignment[unit.unsigned_abs() as usize - 1] =
+                            Some(unit > 0);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+}
+
+/// Value of a literal under a partial assignment
+fn value(assignment: &[Option<bool>], lit: Lit) -> Option<bool> {
+    assignment[lit.unsigned_abs() as usize - 1].map(|value| value == (lit > 0))
+}
+
+impl std::fmt::Display for Cnf {
+    /// The formula in the DIMACS CNF format
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "c {comment}")?;
+        }
+        writeln!(f, "p cnf {} {}", self.n_vars, self.clauses.len())?;
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(f, "{lit} ")?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// Literals that are true if at least `k` of `inputs` are true, for each
+/// `k` from 0 to the number of inputs, using a sequential counter whose
+/// variables are defined by the inputs
+fn at_least(cnf: &mut Cnf, true_lit: Lit, inputs: &[Lit]) -> Vec<Lit> {
+    let mut res = vec![true_lit];
+    for input in inputs {
+        let mut next = vec![true_lit];
+        for k in 1..=res.len() {
+            // At least k of the inputs so far if there were already k, or
+            // there were k - 1 and this input is true
+            let prev = res.get(k).copied().unwrap_or(-true_lit);
+            let carry = res[k - 1];
+            let lit = cnf.new_var();
+            cnf.add_clause([-prev, lit]);
+            cnf.add_clause([-carry, -input, lit]);
+            cnf.add_clause([-lit, prev, carry]);
+            cnf.add_clause([-lit, prev, *input]);
+            next.push(lit);
+        }
+        res = next;
+    }
+    res
+}
+
+/// The self-descriptive numbers in base `base` with `n_pairs` pairs, with
+/// counts written as single digits.
+/// Variable `idx * base + digit + 1` is true if the digit at position `idx`
+/// is `digit`, and the other variables are defined by them.
+/// Pairs are ordered by count and then by digit, both in descending order,
+/// so each number has a single model, matching
+/// [`crate::Solution::expand`].
+pub fn self_descriptive_cnf(n_pairs: usize, base: u8) -> Cnf {
+    let base_usize = base as usize;
+    let n_digits = n_pairs * 2;
+    let mut cnf = Cnf {
+        comments: vec![
+            format!(
+                "self-descriptive numbers with {n_pairs} pairs in base {base}"
+            ),
+            format!(
+                "variable idx * {base} + digit + 1 is true if the digit at position idx is digit"
+            ),
+        ],
+        ..Cnf::default()
+    };
+    let is_digit: Vec<Vec<Lit>> = (0..n_digits)
+        .map(|_| (0..base).map(|_| cnf.new_var()).collect())
+        .collect();
+    // Each position has exactly one digit
+    for digits in &is_digit {
+        cnf.add_clause(digits.iter().copied());
+        for (idx, lhs) in digits.iter().enumerate() {
+            for rhs in &digits[idx + 1..] {
+                cnf.add_clause([-lhs, -rhs]);
+            }
+        }
+    }
+    let true_lit = cnf.new_var();
+    cnf.add_clause([true_lit]);
+    for digit in 0..base_usize {
+        let inputs: Vec<Lit> =
+            is_digit.iter().map(|digits| digits[digit]).collect();
+        let at_least = at_least(&mut cnf, true_lit, &inputs);
+        let at_least = |k: usize| at_least.get(k).copied().unwrap_or(-true_lit);
+        // Each pair describing the digit has its number of occurrences as
+        // its count
+        for pair in is_digit.chunks_exact(2) {
+            for count in 0..base_usize {
+                let is_pair = [-pair[0][count], -pair[1][digit]];
+                cnf.add_clause(is_pair.into_iter().chain([at_least(count)]));
+                cnf.add_clause(
+                    is_pair.into_iter().chain([-at_least(count + 1)]),
+                );
+            }
+        }
+        // Every digit that occurs is described
+        let described = is_digit.chunks_exact(2).map(|pair| pair[1][digit]);
+        cnf.add_clause([-at_least(1)].into_iter().chain(described));
+    }
+    // Pairs in descending order
+    for pairs in is_digit.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+        let [lhs, rhs] = pairs else { unreachable!() };
+        for count in 0..base_usize {
+            for greater_count in count + 1..base_usize {
+                cnf.add_clause([-lhs[0][count], -rhs[0][greater_count]]);
+            }
+            for digit in 0..base_usize {
+                for greater_digit in digit + 1..base_usize {
+                    cnf.add_clause([
+                        -lhs[0][count],
+                        -rhs[0][count],
+                        -lhs[1][digit],
+                        -rhs[1][greater_digit],
+                    ]);
+                }
+            }
+        }
+    }
+    cnf
+}
+
+/// The digits of the number of a model of [`self_descriptive_cnf`]
+pub fn cnf_digits(model: &[bool], n_pairs: usize, base: u8) -> Vec<u8> {
+    model[..n_pairs * 2 * base as usize]
+        .chunks_exact(base as usize)
+        .map(|digits| digits.iter().position(|value| *value).unwrap() as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::solve_iter;
+
+    use super::{Cnf, cnf_digits, self_descriptive_cnf};
+
+    #[test]
+    fn test_models() {
+        // (1 ∨ 2) ∧ (¬1 ∨ ¬2) ∧ (2 ∨ 3)
+        let cnf = Cnf {
+            n_vars: 3,
+            clauses: vec![vec![1, 2], vec![-1, -2], vec![2, 3]],
+            comments: Vec::new(),
+        };
+        let models: BTreeSet<Vec<bool>> = cnf.models().collect();
+        assert_eq!(
+            models,
+            BTreeSet::from([vec![true, false, true], vec![false, true, false]])
+        );
+        let unsatisfiable = Cnf {
+            n_vars: 1,
+            clauses: vec![vec![1], vec![-1]],
+            comments: Vec::new(),
+        };
+        assert_eq!(unsatisfiable.models().count(), 0);
+    }
+
+    #[test]
+    fn test_self_descriptive_cnf() {
+        for (base, max_n_pairs) in [(2, 3), (3, 4), (4, 4), (10, 2)] {
+            for n_pairs in 1..=max_n_pairs {
+                let cnf = self_descriptive_cnf(n_pairs, base);
+                let numbers: Vec<Vec<u8>> = cnf
+                    .models()
+                    .map(|model| cnf_digits(&model, n_pairs, base))
+                    .collect();
+                let expected: BTreeSet<Vec<u8>> = solve_iter(n_pairs, base, ..)
+                    .flat_map(|solution| solution.expand())
+                    .collect();
+                assert_eq!(numbers.len(), expected.len());
+                assert_eq!(
+                    numbers.into_iter().collect::<BTreeSet<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dimacs() {
+        let cnf = self_descriptive_cnf(1, 2);
+        let dimacs = cnf.to_string();
+        let mut lines = dimacs.lines();
+        assert_eq!(
+            lines.next(),
+            Some("c self-descriptive numbers with 1 pairs in base 2")
+        );
+        assert!(lines.next().unwrap().starts_with("c "));
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!("p cnf {} {}", cnf.n_vars, cnf.clauses.len()).as_str()
+            )
+        );
+        assert_eq!(lines.next(), Some("1 2 0"));
+        assert_eq!(lines.count(), cnf.clauses.len() - 1);
+    }
+}
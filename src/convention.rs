@@ -0,0 +1,136 @@
+/// Which component of each pair is written first.
+/// The counting constraints are the same for both conventions, so they
+/// share the search, and only differ in how concrete numbers are written
+/// and read.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Convention {
+    /// Each count is followed by the digit it describes, as in `4442422929`
+    #[default]
+    CountDigit,
+    /// Each digit is followed by its count, as in `4424249292`
+    DigitCount,
+}
+
+impl Convention {
+    pub const ALL: [Self; 2] = [Self::CountDigit, Self::DigitCount];
+
+    /// The (count, digit) pair of two digits written in this convention
+    pub fn pair(self, first: u8, second: u8) -> (u8, u8) {
+        match self {
+            Self::CountDigit => (first, second),
+            Self::DigitCount => (second, first),
+        }
+    }
+
+    /// Write a pair in this convention, given the digits of its count
+    pub fn write_pair(
+        self,
+        count_digits: impl IntoIterator<Item = u8>,
+        digit: u8,
+        res: &mut Vec<u8>,
+    ) {
+        match self {
+            Self::CountDigit => {
+                res.extend(count_digits);
+                res.push(digit);
+            }
+            Self::DigitCount => {
+                res.push(digit);
+                res.extend(count_digits);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Convention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CountDigit => "count-digit",
+            Self::DigitCount => "digit-count",
+        }
+        .fmt(f)
+    }
+}
+
+impl std::str::FromStr for Convention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|convention| convention.to_string() == s)
+            .ok_or_else(|| {
+                let conventions: Vec<_> =
+                    Self::ALL.iter().map(ToString::to_string).collect();
+                format!("expected one of {}", conventions.join(", "))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{Counts, Shard, solve_iter, solve_shard_iter, verify_digits};
+
+    use super::Convention;
+
+    #[test]
+    fn test_digit_count_expansions() {
+        for base in [4, 10] {
+            for n_pairs in 1..=8 {
+                for solution in solve_iter(n_pairs, base, ..) {
+                    // Swapping the digits of each pair gives the same
+                    // numbers as expanding in the digit-count convention
+                    let swapped: BTreeSet<Vec<u8>> = solution
+                        .expand()
+                        .map(|digits| {
+                            digits
+                                .chunks_exact(2)
+                                .flat_map(|pair| [pair[1], pair[0]])
+                                .collect()
+                        })
+                        .collect();
+                    let expansions: Vec<Vec<u8>> = solution
+                        .expand()
+                        .with_convention(Convention::DigitCount)
+                        .collect();
+                    assert_eq!(
+                        expansions.iter().cloned().collect::<BTreeSet<_>>(),
+                        swapped
+                    );
+                    for digits in &expansions {
+                        assert!(
+                            verify_digits(digits, Convention::DigitCount)
+                                .unwrap()
+                                .is_self_descriptive()
+                        );
+                    }
+                }
+            }
+        }
+        // Multi-digit counts are written after the digit they describe
+        let solutions =
+            solve_shard_iter(5, 3, .., Shard::default(), Counts::MultiDigit);
+        for solution in solutions {
+            let expansions = solution
+                .expand()
+                .zip(solution.expand().with_convention(Convention::DigitCount));
+            for (mut count_digit, mut digit_count) in expansions {
+                count_digit.sort();
+                digit_count.sort();
+                assert_eq!(count_digit, digit_count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_convention_parse() {
+        for convention in Convention::ALL {
+            assert_eq!(convention.to_string().parse(), Ok(convention));
+        }
+        assert!("digit".parse::<Convention>().is_err());
+    }
+}
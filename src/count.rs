@@ -0,0 +1,97 @@
+use std::ops::RangeBounds;
+
+use crate::{Solution, unique_digits_range};
+
+/// Number of solutions with a given number of unique digits
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct UniqueDigitsCount {
+    pub n_pairs: usize,
+    pub n_unique_digits: usize,
+    /// Number of solution patterns
+    pub n_solutions: u64,
+    /// Number of concrete numbers, after expanding var slots
+    pub n_numbers: u128,
+}
+
+/// Total number of solutions with a given number of pairs
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PairsCount {
+    pub n_pairs: usize,
+    /// Number of solution patterns
+    pub n_solutions: u64,
+    /// Number of concrete numbers, after expanding var slots
+    pub n_numbers: u128,
+}
+
+impl PairsCount {
+    /// Sum counts for each number of unique digits.
+    /// All counts must have the same number of pairs.
+    pub fn new(counts: &[UniqueDigitsCount]) -> Self {
+        let mut res = Self::default();
+        for count in counts {
+            res.n_pairs = count.n_pairs;
+            res.n_solutions += count.n_solutions;
+            res.n_numbers += count.n_numbers;
+        }
+        res
+    }
+}
+
+/// Count the solutions with `n_pairs` pairs in base `base`, for each number
+/// of unique digits in `unique_digits`.
+/// Every possible number of unique digits in `unique_digits` is included,
+/// even if there are no solutions with that number of unique digits.
+pub fn count_solutions(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    solutions: impl Iterator<Item = Solution>,
+) -> Vec<UniqueDigitsCount> {
+    let unique_digits = unique_digits_range(n_pairs, base, unique_digits);
+    let first_unique_digits = *unique_digits.start();
+    let mut res: Vec<UniqueDigitsCount> = unique_digits
+        .map(|n_unique_digits| UniqueDigitsCount {
+            n_pairs,
+            n_unique_digits,
+            ..Default::default()
+        })
+        .collect();
+    for solution in solutions {
+        let count = &mut res[solution.n_unique_digits() - first_unique_digits];
+        count.n_solutions += 1;
+        count.n_numbers += solution.n_expansions();
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Counts, brute_force, solve_iter};
+
+    use super::count_solutions;
+
+    #[test]
+    fn test_count_solutions() {
+        for n_pairs in 1..=6 {
+            let counts =
+                count_solutions(n_pairs, 10, .., solve_iter(n_pairs, 10, ..));
+            assert_eq!(counts.len(), n_pairs);
+            let n_numbers: u128 =
+                counts.iter().map(|count| count.n_numbers).sum();
+            assert_eq!(
+                n_numbers,
+                brute_force(n_pairs, 10, Counts::SingleDigit).len() as u128
+            );
+        }
+        let counts = count_solutions(12, 10, .., solve_iter(12, 10, ..));
+        assert_eq!(counts[2].n_solutions, 3);
+        assert_eq!(counts[2].n_numbers, 10);
+        let counts = count_solutions(12, 10, 3..=4, solve_iter(12, 10, 3..=4));
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].n_unique_digits, 3);
+        assert_eq!(counts[0].n_numbers, 10);
+        assert_eq!(counts[1].n_solutions, 57);
+    }
+}
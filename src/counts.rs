@@ -0,0 +1,729 @@
+//! How counts are written, and the digits they contribute to a number.
+
+use std::ops::RangeInclusive;
+
+use arrayvec::ArrayVec;
+
+use crate::{MAX_BASE, digit_char, var_ident};
+
+/// Digits of a count in base `base`, most significant first
+pub fn count_digits(count: u8, base: u8) -> ArrayVec<u8, 8> {
+    let mut res = ArrayVec::new();
+    let mut count = count;
+    loop {
+        res.push(count % base);
+        count /= base;
+        if count == 0 {
+            break;
+        }
+    }
+    res.reverse();
+    res
+}
+
+/// Render a count in base `base`
+pub fn count_string(count: u8, base: u8) -> String {
+    count_digits(count, base)
+        .into_iter()
+        .map(digit_char)
+        .collect()
+}
+
+/// Render a sequence of counts in base `base`.
+/// If any count needs several digits, the counts are separated by spaces.
+pub fn counts_string(counts: impl IntoIterator<Item = u8>, base: u8) -> String {
+    let counts: Vec<u8> = counts.into_iter().collect();
+    let separator = if counts.iter().all(|count| *count < base) {
+        ""
+    } else {
+        " "
+    };
+    let counts: Vec<String> = counts
+        .into_iter()
+        .map(|count| count_string(count, base))
+        .collect();
+    counts.join(separator)
+}
+
+/// Digits written for a count, most significant first
+pub type CountDigits = ArrayVec<u8, 24>;
+
+/// English words for a count, joined by hyphens, such as
+/// `one-hundred-twenty-three`
+pub fn count_word(count: u8) -> String {
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy",
+        "eighty", "ninety",
+    ];
+    let mut words = Vec::new();
+    let hundreds = count / 100;
+    let rest = count % 100;
+    if hundreds != 0 {
+        words.extend([ONES[hundreds as usize], "hundred"]);
+    }
+    if rest >= 20 {
+        words.push(TENS[(rest / 10) as usize]);
+        if !rest.is_multiple_of(10) {
+            words.push(ONES[(rest % 10) as usize]);
+        }
+    } else if rest != 0 || hundreds == 0 {
+        words.push(ONES[rest as usize]);
+    }
+    words.join("-")
+}
+
+/// Render a letter digit, where `a` is 0 and `z` is 25
+pub fn letter_char(digit: u8) -> char {
+    const LOWERCASE_A_ASCII: u8 = 0x61;
+    char::from(LOWERCASE_A_ASCII + digit)
+}
+
+/// How the count of each pair is written, which decides the digits that
+/// each count contributes to a number.
+/// The built-in renderers are selected by [`Counts`], and any other
+/// renderer is searched with [`Counts::Custom`].
+pub trait CountRenderer: std::fmt::Debug + Send + Sync {
+    /// Digits written for a count in a number in base `base`, each less
+    /// than `base`
+    fn digits(&self, count: u8, base: u8) -> CountDigits;
+
+    /// Render a count in a number in base `base`
+    fn render(&self, count: u8, base: u8) -> String {
+        self.digits(count, base)
+            .into_iter()
+            .map(|digit| self.digit_char(digit))
+            .collect()
+    }
+
+    /// Render a digit of a number
+    fn digit_char(&self, digit: u8) -> char {
+        digit_char(digit)
+    }
+
+    /// Identifier for the `idx`'th var slot in a rendered solution, which
+    /// is not a digit
+    fn var_ident(&self, idx: u8) -> char {
+        var_ident(idx)
+    }
+
+    /// Greatest number of digits in a number with `n_pairs` pairs in base
+    /// `base`.
+    /// A number has a digit for each pair, and the digits of each count,
+    /// where every count is at most the number of digits. The bound starts
+    /// from the longest count of any supported count, and is tightened to
+    /// the longest count up to the bound until it no longer changes.
+    fn max_n_digits(&self, n_pairs: usize, base: u8) -> usize {
+        let mut res = usize::MAX;
+        loop {
+            let max_count = res.min(u8::MAX as usize) as u8;
+            let len = (1..=max_count)
+                .map(|count| self.digits(count, base).len())
+                .max()
+                .unwrap_or(0);
+            let next = n_pairs * (len + 1);
+            if next >= res {
+                return res;
+            }
+            res = next;
+        }
+    }
+
+    /// Fewest digits in a number with `n_pairs` pairs in base `base`, where
+    /// every count is at least 1
+    fn min_n_digits(&self, n_pairs: usize, base: u8) -> usize {
+        let len = (1..=self.max_count(n_pairs, base))
+            .map(|count| self.digits(count, base).len())
+            .min()
+            .unwrap_or(1);
+        n_pairs * (len + 1)
+    }
+
+    /// Greatest count, and greatest number of repetitions of a pair, in a
+    /// number with `n_pairs` pairs in base `base`
+    fn max_count(&self, n_pairs: usize, base: u8) -> u8 {
+        self.max_n_digits(n_pairs, base).min(u8::MAX as usize) as u8
+    }
+
+    /// Whether every count of a number with `n_pairs` pairs in base `base`
+    /// is supported, since counts greater than `u8::MAX` are not
+    fn supports(&self, n_pairs: usize, base: u8) -> bool {
+        self.max_n_digits(n_pairs, base) <= u8::MAX as usize
+    }
+}
+
+/// Every count is a single digit, so no digit occurs `base` or more times
+#[derive(Clone, Copy, Debug)]
+pub struct SingleDigitCounts;
+
+impl CountRenderer for SingleDigitCounts {
+    fn digits(&self, count: u8, base: u8) -> CountDigits {
+        count_digits(count, base).into_iter().collect()
+    }
+
+    fn max_n_digits(&self, n_pairs: usize, _base: u8) -> usize {
+        n_pairs * 2
+    }
+
+    fn max_count(&self, _n_pairs: usize, base: u8) -> u8 {
+        base - 1
+    }
+
+    /// Single-digit counts are at most `base - 1` however many pairs there
+    /// are
+    fn supports(&self, _n_pairs: usize, _base: u8) -> bool {
+        true
+    }
+}
+
+/// Counts of `base` or more are written with as many digits as they need
+#[derive(Clone, Copy, Debug)]
+pub struct MultiDigitCounts;
+
+impl CountRenderer for MultiDigitCounts {
+    fn digits(&self, count: u8, base: u8) -> CountDigits {
+        count_digits(count, base).into_iter().collect()
+    }
+
+    fn max_n_digits(&self, n_pairs: usize, base: u8) -> usize {
+        BaseCounts(base).max_n_digits(n_pairs, base)
+    }
+}
+
+/// Counts are written in another base, with as many digits as they need
+#[derive(Clone, Copy, Debug)]
+pub struct BaseCounts(u8);
+
+impl BaseCounts {
+    /// Counts written in base `count_base`, or `None` unless it is from 2
+    /// to [`MAX_BASE`]
+    pub fn new(count_base: u8) -> Option<Self> {
+        (2..=MAX_BASE as u8)
+            .contains(&count_base)
+            .then_some(Self(count_base))
+    }
+
+    pub fn count_base(self) -> u8 {
+        self.0
+    }
+}
+
+impl CountRenderer for BaseCounts {
+    fn digits(&self, count: u8, _base: u8) -> CountDigits {
+        count_digits(count, self.0).into_iter().collect()
+    }
+
+    /// If the longest count has `len` digits, then `base^(len - 1)` is at
+    /// most `n_pairs * (len + 1)`
+    fn max_n_digits(&self, n_pairs: usize, _base: u8) -> usize {
+        let mut len = 1;
+        while (self.0 as usize)
+            .checked_pow(len)
+            .is_some_and(|min_count| min_count <= n_pairs * (len as usize + 2))
+        {
+            len += 1;
+        }
+        n_pairs * (len as usize + 1)
+    }
+}
+
+/// Renderers of counts in each base from 2, so that [`Counts::Base`] has a
+/// renderer without allocating
+static BASE_COUNTS: [BaseCounts; MAX_BASE - 1] = {
+    let mut res = [BaseCounts(2); MAX_BASE - 1];
+    let mut idx = 0;
+    while idx < res.len() {
+        res[idx] = BaseCounts(idx as u8 + 2);
+        idx += 1;
+    }
+    res
+};
+
+/// The renderer of [`Counts::Base`]
+fn base_counts(count_base: u8) -> BaseCounts {
+    BaseCounts::new(count_base).unwrap_or_else(|| {
+        panic!("counts cannot be written in base {count_base}")
+    })
+}
+
+/// Counts are spelled out as English words, and the digits of a number are
+/// the letters `a` to `z` of its sentence
+#[derive(Clone, Copy, Debug)]
+pub struct WordCounts;
+
+impl CountRenderer for WordCounts {
+    fn digits(&self, count: u8, _base: u8) -> CountDigits {
+        count_word(count)
+            .bytes()
+            .filter(|byte| *byte != b'-')
+            .map(|byte| byte - b'a')
+            .collect()
+    }
+
+    fn render(&self, count: u8, _base: u8) -> String {
+        count_word(count)
+    }
+
+    fn digit_char(&self, digit: u8) -> char {
+        letter_char(digit)
+    }
+
+    /// Uppercase, so that it is not a letter of a word
+    fn var_ident(&self, idx: u8) -> char {
+        var_ident(idx).to_ascii_uppercase()
+    }
+}
+
+/// Which [`CountRenderer`] writes the count of each pair.
+/// The built-in renderers are serialized by name, while custom renderers
+/// are not serialized, so checkpoints and records need a built-in renderer.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Counts {
+    /// Every count is a single digit, so no digit occurs `base` or more
+    /// times, and every number has twice as many digits as pairs
+    #[default]
+    SingleDigit,
+    /// Counts of `base` or more are written with as many digits as they
+    /// need, as in [`crate::describe`]
+    MultiDigit,
+    /// Counts are written in another base, which is at most the base of the
+    /// numbers, with as many digits as they need.
+    /// The base of the counts is from 2 to [`MAX_BASE`], as checked by
+    /// [`Counts::base`], and the methods of [`Counts`] panic otherwise.
+    Base(u8),
+    /// Counts are spelled out as English words, and the digits of a number
+    /// are the letters `a` to `z` of its sentence, so the base is 26
+    Words,
+    /// Counts are written by a renderer outside this crate
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(&'static dyn CountRenderer),
+}
+
+impl PartialEq for Counts {
+    /// Custom renderers are equal if they are the same renderer
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Base(lhs), Self::Base(rhs)) => lhs == rhs,
+            (Self::Custom(lhs), Self::Custom(rhs)) => {
+                std::ptr::addr_eq(*lhs, *rhs)
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Counts {}
+
+/// Call a method of the renderer of `counts`, without dynamic dispatch for
+/// the built-in renderers, which are used in the innermost loops of the
+/// search
+macro_rules! with_renderer {
+    ($counts:expr, $renderer:ident => $body:expr) => {
+        match $counts {
+            Counts::SingleDigit => {
+                let $renderer = SingleDigitCounts;
+                $body
+            }
+            Counts::MultiDigit => {
+                let $renderer = MultiDigitCounts;
+                $body
+            }
+            Counts::Base(count_base) => {
+                let $renderer = base_counts(count_base);
+                $body
+            }
+            Counts::Words => {
+                let $renderer = WordCounts;
+                $body
+            }
+            Counts::Custom($renderer) => $body,
+        }
+    };
+}
+
+impl Counts {
+    /// Counts written in base `count_base`, or `None` unless it is from 2
+    /// to [`MAX_BASE`]
+    pub fn base(count_base: u8) -> Option<Self> {
+        BaseCounts::new(count_base).map(|_| Self::Base(count_base))
+    }
+
+    /// The renderer that writes counts
+    pub fn renderer(self) -> &'static dyn CountRenderer {
+        match self {
+            Self::SingleDigit => &SingleDigitCounts,
+            Self::MultiDigit => &MultiDigitCounts,
+            Self::Base(count_base) => {
+                &BASE_COUNTS[base_counts(count_base).count_base() as usize - 2]
+            }
+            Self::Words => &WordCounts,
+            Self::Custom(renderer) => renderer,
+        }
+    }
+
+    /// Digits written for a count in a number in base `base`
+    pub fn digits(self, count: u8, base: u8) -> CountDigits {
+        with_renderer!(self, renderer => renderer.digits(count, base))
+    }
+
+    /// Render a count in a number in base `base`
+    pub fn render(self, count: u8, base: u8) -> String {
+        with_renderer!(self, renderer => renderer.render(count, base))
+    }
+
+    /// Render a digit of a number, which is a letter if counts are words
+    pub fn digit_char(self, digit: u8) -> char {
+        with_renderer!(self, renderer => renderer.digit_char(digit))
+    }
+
+    /// Identifier for the `idx`'th var slot in a rendered solution, which
+    /// is uppercase if counts are words, so that it is not a digit
+    pub fn var_ident(self, idx: u8) -> char {
+        with_renderer!(self, renderer => renderer.var_ident(idx))
+    }
+
+    /// Whether counts are written as digits in the base of the numbers
+    pub fn is_digits(&self) -> bool {
+        matches!(self, Self::SingleDigit | Self::MultiDigit)
+    }
+
+    /// Whether a count is written as a single digit in base `base`
+    pub fn is_single_digit(self, count: u8, base: u8) -> bool {
+        self.digits(count, base).len() == 1
+    }
+
+    /// Greatest number of digits in a number with `n_pairs` pairs in base
+    /// `base`
+    pub fn max_n_digits(self, n_pairs: usize, base: u8) -> usize {
+        with_renderer!(self, renderer => renderer.max_n_digits(n_pairs, base))
+    }
+
+    /// Possible numbers of digits in a number with `n_pairs` pairs in base
+    /// `base`, which are the possible sums of the descriptors
+    pub fn n_digits_range(
+        self,
+        n_pairs: usize,
+        base: u8,
+    ) -> RangeInclusive<usize> {
+        with_renderer!(self, renderer => {
+            renderer.min_n_digits(n_pairs, base)
+                ..=renderer.max_n_digits(n_pairs, base)
+        })
+    }
+
+    /// Whether every count of a number with `n_pairs` pairs in base `base`
+    /// is supported, since counts greater than `u8::MAX` are not
+    pub fn supports(self, n_pairs: usize, base: u8) -> bool {
+        with_renderer!(self, renderer => renderer.supports(n_pairs, base))
+    }
+
+    /// Greatest count, and greatest number of repetitions of a pair, in a
+    /// number with `n_pairs` pairs in base `base`.
+    /// Counts greater than `u8::MAX` are not supported, so the search is
+    /// only complete if [`Self::supports`] the pair count.
+    pub fn max_count(self, n_pairs: usize, base: u8) -> u8 {
+        with_renderer!(self, renderer => renderer.max_count(n_pairs, base))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        MAX_BASE, Shard, Solution, brute_force, solve_iter, solve_shard_iter,
+    };
+
+    use super::{
+        CountDigits, CountRenderer, Counts, count_digits, count_word,
+        counts_string,
+    };
+
+    /// Counts in base 3, least significant digit first
+    #[derive(Debug)]
+    struct ReversedBase3;
+
+    impl CountRenderer for ReversedBase3 {
+        fn digits(&self, count: u8, _base: u8) -> CountDigits {
+            count_digits(count, 3).into_iter().rev().collect()
+        }
+    }
+
+    fn solve_multi_digit(n_pairs: usize, base: u8) -> Vec<Solution> {
+        solve_shard_iter(
+            n_pairs,
+            base,
+            ..,
+            Shard::default(),
+            Counts::MultiDigit,
+        )
+        .collect()
+    }
+
+    #[test]
+    fn test_count_digits() {
+        assert_eq!(count_digits(0, 10).as_slice(), [0]);
+        assert_eq!(count_digits(12, 10).as_slice(), [1, 2]);
+        assert_eq!(count_digits(255, 2).as_slice(), [1; 8]);
+        assert_eq!(counts_string([1, 2, 9], 10), "129");
+        assert_eq!(counts_string([1, 12, 9], 10), "1 12 9");
+    }
+
+    #[test]
+    fn test_multi_digit_counts() {
+        let mut n_multi_digit = 0;
+        for base in 2..=4 {
+            for n_pairs in 1..=5 {
+                let solutions = solve_multi_digit(n_pairs, base);
+                assert!(
+                    solutions
+                        .windows(2)
+                        .all(|pair| pair[0].work_item() <= pair[1].work_item())
+                );
+                let expanded: Vec<Vec<u8>> = solutions
+                    .iter()
+                    .flat_map(|solution| solution.expand())
+                    .collect();
+                let actual: BTreeSet<Vec<u8>> =
+                    expanded.iter().cloned().collect();
+                assert_eq!(actual.len(), expanded.len());
+                assert_eq!(
+                    actual,
+                    brute_force(n_pairs, base, Counts::MultiDigit),
+                    "{n_pairs} pairs in base {base}"
+                );
+                n_multi_digit += actual
+                    .iter()
+                    .filter(|digits| digits.len() > n_pairs * 2)
+                    .count();
+            }
+        }
+        assert!(n_multi_digit > 0);
+    }
+
+    #[test]
+    fn test_multi_digit_counts_extend_single_digit() {
+        // Every solution with single-digit counts is still found
+        for n_pairs in [5, 8] {
+            let multi: Vec<String> = solve_multi_digit(n_pairs, 10)
+                .iter()
+                .map(|solution| solution.to_string())
+                .collect();
+            for solution in solve_iter(n_pairs, 10, ..) {
+                assert!(multi.contains(&solution.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_supports() {
+        // Single-digit counts never exceed the base, however many pairs
+        assert!(Counts::SingleDigit.supports(128, 10));
+        assert!(Counts::SingleDigit.supports(200, 10));
+        assert!(Counts::SingleDigit.supports(130, 36));
+        assert_eq!(solve_iter(200, 10, ..).count(), 0);
+        assert!(Counts::MultiDigit.supports(60, 10));
+        assert!(!Counts::MultiDigit.supports(128, 10));
+        assert!(!Counts::Base(2).supports(128, 36));
+        assert!(!Counts::Words.supports(128, 26));
+    }
+
+    #[test]
+    fn test_count_word() {
+        assert_eq!(count_word(5), "five");
+        assert_eq!(count_word(40), "forty");
+        assert_eq!(count_word(77), "seventy-seven");
+        assert_eq!(count_word(100), "one-hundred");
+        assert_eq!(count_word(213), "two-hundred-thirteen");
+        assert_eq!(Counts::Words.digits(3, 26).as_slice(), [19, 7, 17, 4, 4]);
+    }
+
+    #[test]
+    fn test_count_base() {
+        for base in 3..=4 {
+            for count_base in 2..=base {
+                let counts = Counts::Base(count_base);
+                for n_pairs in 1..=4 {
+                    let solutions: Vec<Solution> = solve_shard_iter(
+                        n_pairs,
+                        base,
+                        ..,
+                        Shard::default(),
+                        counts,
+                    )
+                    .collect();
+                    let actual: BTreeSet<Vec<u8>> = solutions
+                        .iter()
+                        .flat_map(|solution| solution.expand())
+                        .collect();
+                    assert_eq!(
+                        actual,
+                        brute_force(n_pairs, base, counts),
+                        "{n_pairs} pairs in base {base}, counts in base \
+                         {count_base}"
+                    );
+                }
+            }
+        }
+        // Counts in the base of the numbers are the same as multi-digit
+        // counts
+        let multi: Vec<String> = solve_multi_digit(5, 3)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let base_3: Vec<String> =
+            solve_shard_iter(5, 3, .., Shard::default(), Counts::Base(3))
+                .map(|solution| solution.to_string())
+                .collect();
+        assert_eq!(multi, base_3);
+        for count_base in [0, 1, MAX_BASE as u8 + 1] {
+            assert_eq!(Counts::base(count_base), None);
+        }
+        assert_eq!(Counts::base(2), Some(Counts::Base(2)));
+        assert_eq!(
+            Counts::base(2).unwrap().renderer().digits(5, 4).as_slice(),
+            [1, 0, 1]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "counts cannot be written in base 1")]
+    fn test_invalid_count_base() {
+        Counts::Base(1).max_n_digits(4, 10);
+    }
+
+    #[test]
+    fn test_custom_counts() {
+        let counts = Counts::Custom(&ReversedBase3);
+        assert_eq!(counts, Counts::Custom(&ReversedBase3));
+        assert_ne!(counts, Counts::Base(3));
+        assert_eq!(counts.render(5, 4), "21");
+        assert_eq!(
+            counts.n_digits_range(4, 4),
+            Counts::Base(3).n_digits_range(4, 4)
+        );
+        let mut n_reversed = 0;
+        for n_pairs in 1..=4 {
+            let actual: BTreeSet<Vec<u8>> =
+                solve_shard_iter(n_pairs, 4, .., Shard::default(), counts)
+                    .flat_map(|solution| solution.expand())
+                    .collect();
+            let expected = brute_force(n_pairs, 4, counts);
+            assert_eq!(actual, expected, "{n_pairs} pairs");
+            n_reversed += expected
+                .difference(&brute_force(n_pairs, 4, Counts::Base(3)))
+                .count();
+        }
+        assert!(n_reversed > 0);
+    }
+
+    #[test]
+    fn test_word_counts() {
+        for n_pairs in 1..=4 {
+            let solutions: Vec<Solution> = solve_shard_iter(
+                n_pairs,
+                26,
+                ..,
+                Shard::default(),
+                Counts::Words,
+            )
+            .collect();
+            // Every sentence counts its own letters
+            for solution in &solutions {
+                for pairs in solution.expand().pairs() {
+                    let mut letter_counts = [0u8; 26];
+                    for (count, letter) in &pairs {
+                        letter_counts[*letter as usize] += 1;
+                        for digit in Counts::Words.digits(*count, 26) {
+                            letter_counts[digit as usize] += 1;
+                        }
+                    }
+                    for (count, letter) in &pairs {
+                        assert_eq!(letter_counts[*letter as usize], *count);
+                    }
+                }
+            }
+            // Sentences that describe each letter once are determined by
+            // their counts, so every multiset of counts whose words have
+            // the right letters is found
+            let actual: BTreeSet<Vec<u8>> = solutions
+                .iter()
+                .filter(|solution| solution.n_unique_digits() == n_pairs)
+                .map(|solution| solution.work_item().descriptors)
+                .collect();
+            let max_count = Counts::Words.max_count(n_pairs, 26);
+            let mut expected = BTreeSet::new();
+            let mut counts = Vec::new();
+            word_count_multisets(
+                n_pairs,
+                1,
+                max_count,
+                &mut counts,
+                &mut expected,
+            );
+            assert_eq!(actual, expected, "{n_pairs} pairs");
+        }
+    }
+
+    /// Find every ascending sequence of `n_pairs` counts, each at least
+    /// `min_count`, where the letters described by more than one count are
+    /// those that occur in the words of the counts
+    fn word_count_multisets(
+        n_pairs: usize,
+        min_count: u8,
+        max_count: u8,
+        counts: &mut Vec<u8>,
+        res: &mut BTreeSet<Vec<u8>>,
+    ) {
+        if counts.len() == n_pairs {
+            let mut letter_counts = [0u8; 26];
+            for count in counts.iter() {
+                for digit in Counts::Words.digits(*count, 26) {
+                    letter_counts[digit as usize] += 1;
+                }
+            }
+            let mut described: Vec<u8> = letter_counts
+                .iter()
+                .filter(|count| **count != 0)
+                .map(|count| count + 1)
+                .collect();
+            if described.len() > n_pairs {
+                return;
+            }
+            described.resize(n_pairs, 1);
+            described.sort();
+            if described == *counts {
+                res.insert(counts.clone());
+            }
+            return;
+        }
+        for count in min_count..=max_count {
+            counts.push(count);
+            word_count_multisets(n_pairs, count, max_count, counts, res);
+            counts.pop();
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt::Write as _;
+
+use crate::{
+    MAX_BASE, PartitionsParts, digit_char, expand::next_combination, var_ident,
+};
+
+/// Counts of the next number under the describe map, given the counts of
+/// the digits of a number, sorted in ascending order.
+/// Every digit that occurs as a count is described, so each is counted once
+/// for its own pair, and once for each digit whose count it is. The other
+/// described digits occur only in their own pairs, so they are counted once.
+fn next_counts(counts: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(counts.len());
+    for (idx, count) in counts.iter().enumerate() {
+        if idx == 0 || counts[idx - 1] != *count {
+            let n_repeats = counts[idx..].iter().take_while(|c| *c == count);
+            res.push(1 + n_repeats.count() as u8);
+        }
+    }
+    res.resize(counts.len(), 1);
+    res.sort();
+    res
+}
+
+/// A cycle of the describe map, where describing each number gives the next
+/// number, and describing the last number gives the first.
+/// Every number in a cycle describes the same set of digits, which includes
+/// every digit that occurs as a count. Any other digits are free, and can be
+/// chosen from the digits that do not occur as counts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Cycle {
+    /// Counts of the digits of each number in the cycle, in ascending order.
+    /// The first number is the one whose counts are smallest.
+    pub counts: Vec<Vec<u8>>,
+    /// Digits that occur as counts in some number of the cycle, in
+    /// ascending order
+    pub count_digits: Vec<u8>,
+    /// Numeral base of the numbers
+    pub base: u8,
+}
+
+impl Cycle {
+    /// Find the cycle of `counts`, if its counts are the smallest in a
+    /// cycle of at most `max_cycle_len` numbers
+    fn new(counts: Vec<u8>, base: u8, max_cycle_len: usize) -> Option<Self> {
+        let mut cycle = vec![counts];
+        loop {
+            let next = next_counts(cycle.last().unwrap());
+            if next == cycle[0] {
+                break;
+            }
+            // Counts that need several digits, a cycle that does not
+            // return to `counts`, or a cycle that is found from its
+            // smallest counts instead
+            if next.last().is_some_and(|count| *count >= base)
+                || cycle.len() == max_cycle_len
+                || next < cycle[0]
+                || cycle.contains(&next)
+            {
+                return None;
+            }
+            cycle.push(next);
+        }
+        let mut is_count = [false; MAX_BASE];
+        for count in cycle.iter().flatten() {
+            is_count[*count as usize] = true;
+        }
+        let count_digits: Vec<u8> = (0..base)
+            .filter(|digit| is_count[*digit as usize])
+            .collect();
+        if count_digits.len() > cycle[0].len() {
+            return None;
+        }
+        Some(Self {
+            counts: cycle,
+            count_digits,
+            base,
+        })
+    }
+
+    pub fn n_pairs(&self) -> usize {
+        self.counts[0].len()
+    }
+
+    /// Number of numbers in the cycle, which is 1 for a fixed point
+    pub fn cycle_len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Number of described digits that do not occur as counts
+    pub fn n_free_digits(&self) -> usize {
+        self.n_pairs() - self.count_digits.len()
+    }
+
+    /// Digits that may be chosen as free digits, in ascending order
+    pub fn free_digits(&self) -> Vec<u8> {
+        (0..self.base)
+            .filter(|digit| !self.count_digits.contains(digit))
+            .collect()
+    }
+
+    /// Count of each digit in the number at `idx`, for every described digit
+    /// `digit`, as a `(count, digit)` pair.
+    /// Pairs are ordered by count and then by digit, both in descending
+    /// order, as in [`crate::describe`].
+    fn pairs(&self, idx: usize, digits: &[u8]) -> Vec<(u8, u8)> {
+        let prev =
+            &self.counts[(idx + self.cycle_len() - 1) % self.cycle_len()];
+        let mut res: Vec<(u8, u8)> = digits
+            .iter()
+            .map(|digit| {
+                let n_counts = prev.iter().filter(|c| *c == digit).count();
+                (1 + n_counts as u8, *digit)
+            })
+            .collect();
+        res.sort_by_key(|pair| std::cmp::Reverse(*pair));
+        res
+    }
+
+    /// Iterate over every concrete cycle, as the digits of each number in
+    /// the cycle, for each choice of free digits
+    pub fn expand(&self) -> impl Iterator<Item = Vec<Vec<u8>>> + '_ {
+        let free_digits = self.free_digits();
+        let mut chosen: Option<Vec<usize>> =
+            Some((0..self.n_free_digits()).collect());
+        std::iter::from_fn(move || {
+            let idxs = chosen.as_mut()?;
+            let mut digits = self.count_digits.clone();
+            digits.extend(idxs.iter().map(|idx| free_digits[*idx]));
+            let res = (0..self.cycle_len())
+                .map(|idx| {
+                    self.pairs(idx, &digits)
+                        .into_iter()
+                        .flat_map(|(count, digit)| [count, digit])
+                        .collect()
+                })
+                .collect();
+            if !next_combination(idxs, free_digits.len()) {
+                chosen = None;
+            }
+            Some(res)
+        })
+    }
+
+    /// Number of concrete cycles, without enumerating them
+    pub fn count_expansions(&self) -> u128 {
+        let n = self.free_digits().len();
+        let k = self.n_free_digits();
+        (0..k).fold(1, |acc, idx| acc * (n - idx) as u128 / (idx + 1) as u128)
+    }
+}
+
+impl std::fmt::Display for Cycle {
+    /// Free digits are rendered as idents, after the pairs of digits that
+    /// occur as counts
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "length {}:", self.cycle_len())?;
+        let idents: Vec<char> =
+            (0..self.n_free_digits() as u8).map(var_ident).collect();
+        for idx in 0..self.cycle_len() {
+            let mut number = String::new();
+            for (count, digit) in self.pairs(idx, &self.count_digits) {
+                number.push(digit_char(count));
+                number.push(digit_char(digit));
+            }
+            for ident in &idents {
+                write!(number, "1{ident}")?;
+            }
+            writeln!(f, "  {number}")?;
+        }
+        if !idents.is_empty() {
+            "where\n  {".fmt(f)?;
+            let idents: Vec<String> =
+                idents.iter().map(char::to_string).collect();
+            idents.join(", ").fmt(f)?;
+            "} ⊆ {".fmt(f)?;
+            let free_digits: Vec<String> = self
+                .free_digits()
+                .into_iter()
+                .map(|digit| digit_char(digit).to_string())
+                .collect();
+            free_digits.join(", ").fmt(f)?;
+            "}\n".fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterate over the cycles of the describe map of at most `max_cycle_len`
+/// numbers, each with `n_pairs` pairs, in base `base`.
+/// Cycles of length 1 are the self-descriptive numbers whose pairs describe
+/// distinct digits.
+/// The counts of the next number depend only on the counts of a number, so
+/// the search follows partitions of `2 * n_pairs` into `n_pairs` counts.
+pub fn solve_cycles(
+    n_pairs: usize,
+    base: u8,
+    max_cycle_len: usize,
+) -> impl Iterator<Item = Cycle> {
+    let partitions = (n_pairs <= base as usize)
+        .then(|| PartitionsParts::new(n_pairs, n_pairs * 2, base - 1));
+    partitions
+        .into_iter()
+        .flatten()
+        .filter_map(move |counts| Cycle::new(counts, base, max_cycle_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{describe, describe_trajectory, solve_iter};
+
+    use super::solve_cycles;
+
+    #[test]
+    fn test_solve_cycles() {
+        for base in [4, 10] {
+            for n_pairs in 1..=base as usize {
+                let mut fixed_points = BTreeSet::new();
+                for cycle in solve_cycles(n_pairs, base, 4) {
+                    let mut n_expansions = 0;
+                    for numbers in cycle.expand() {
+                        n_expansions += 1;
+                        for (idx, number) in numbers.iter().enumerate() {
+                            let next = &numbers[(idx + 1) % numbers.len()];
+                            assert_eq!(&describe(number, base), next);
+                        }
+                        let trajectory = describe_trajectory(&numbers[0], base);
+                        assert_eq!(trajectory.cycle_len(), cycle.cycle_len());
+                        if let [number] = &numbers[..] {
+                            fixed_points.insert(number.clone());
+                        }
+                    }
+                    assert_eq!(n_expansions, cycle.count_expansions());
+                }
+                if n_pairs <= 8 {
+                    let expected: BTreeSet<_> =
+                        solve_iter(n_pairs, base, n_pairs..=n_pairs)
+                            .flat_map(|solution| solution.expand())
+                            .collect();
+                    assert_eq!(fixed_points, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_cycles_complete() {
+        // Every cycle reached from a small number is found by the search
+        let base = 5;
+        let mut cycles = BTreeSet::new();
+        for n_pairs in 1..=base as usize {
+            for cycle in solve_cycles(n_pairs, base, usize::MAX) {
+                for numbers in cycle.expand() {
+                    cycles.insert(numbers.into_iter().collect::<BTreeSet<_>>());
+                }
+            }
+        }
+        let mut digits = vec![0u8; 6];
+        for start in 0..5usize.pow(6) {
+            let mut n = start;
+            for digit in digits.iter_mut() {
+                *digit = (n % 5) as u8;
+                n /= 5;
+            }
+            let trajectory = describe_trajectory(&digits, base);
+            // Cycles with counts of several digits are not searched
+            let single_digit_counts = trajectory.cycle().iter().all(|number| {
+                let distinct: BTreeSet<_> = number.iter().collect();
+                describe(number, base).len() == distinct.len() * 2
+            });
+            if single_digit_counts {
+                let cycle: BTreeSet<_> =
+                    trajectory.cycle().iter().cloned().collect();
+                assert!(cycles.contains(&cycle), "{cycle:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use crate::MAX_BASE;
+
+/// Digits of `n` in base `base`, most significant first
+fn base_digits(mut n: usize, base: u8) -> Vec<u8> {
+    let mut res = Vec::new();
+    loop {
+        res.push((n % base as usize) as u8);
+        n /= base as usize;
+        if n == 0 {
+            break;
+        }
+    }
+    res.reverse();
+    res
+}
+
+/// Apply the describe map to a number in base `base`: write a
+/// (count, digit) pair for each digit that occurs in the number, with pairs
+/// ordered by count and then by digit, both in descending order, as in
+/// [`crate::Solution::expand`].
+/// Counts that do not fit in a single digit are written with as many
+/// digits as they need.
+/// Each digit is described by a single pair, so the fixed points of the map
+/// with single-digit counts are the self-descriptive numbers whose pairs
+/// describe distinct digits.
+pub fn describe(digits: &[u8], base: u8) -> Vec<u8> {
+    let mut counts = [0usize; MAX_BASE];
+    for digit in digits {
+        counts[*digit as usize] += 1;
+    }
+    let mut pairs: Vec<(usize, u8)> = (0..base)
+        .filter(|digit| counts[*digit as usize] != 0)
+        .map(|digit| (counts[digit as usize], digit))
+        .collect();
+    pairs.sort_by_key(|pair| std::cmp::Reverse(*pair));
+    let mut res = Vec::new();
+    for (count, digit) in pairs {
+        res.extend(base_digits(count, base));
+        res.push(digit);
+    }
+    res
+}
+
+/// The numbers visited by repeatedly applying the describe map, until a
+/// number repeats
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trajectory {
+    /// Each distinct number visited, starting with the initial number
+    pub numbers: Vec<Vec<u8>>,
+    /// Index in `numbers` of the first number in the cycle
+    pub cycle_start: usize,
+}
+
+impl Trajectory {
+    /// Number of steps taken before reaching the cycle
+    pub fn trajectory_len(&self) -> usize {
+        self.cycle_start
+    }
+
+    /// Number of numbers in the cycle, which is 1 for a fixed point
+    pub fn cycle_len(&self) -> usize {
+        self.numbers.len() - self.cycle_start
+    }
+
+    /// The numbers in the cycle
+    pub fn cycle(&self) -> &[Vec<u8>] {
+        &self.numbers[self.cycle_start..]
+    }
+}
+
+/// Iterate the describe map from `digits` in base `base` until it reaches a
+/// fixed point or a cycle.
+/// Every number after the first has at most `base` pairs, with counts no
+/// larger than the length of the previous number, so the trajectory always
+/// reaches a cycle.
+pub fn describe_trajectory(digits: &[u8], base: u8) -> Trajectory {
+    let mut idxs = HashMap::new();
+    let mut numbers = Vec::new();
+    let mut number = digits.to_vec();
+    loop {
+        if let Some(cycle_start) = idxs.get(&number) {
+            return Trajectory {
+                numbers,
+                cycle_start: *cycle_start,
+            };
+        }
+        idxs.insert(number.clone(), numbers.len());
+        let next = describe(&number, base);
+        numbers.push(number);
+        number = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{Counts, brute_force, solve_iter, verify::parse_digits};
+
+    use super::{describe, describe_trajectory};
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&[1, 2, 1, 1], 10), [3, 1, 1, 2]);
+        assert_eq!(describe(&[0; 12], 10), [1, 2, 0]);
+        assert_eq!(describe(&[0; 12], 16), [12, 0]);
+        for base in [4, 10] {
+            for n_pairs in 1..=8 {
+                for solution in solve_iter(n_pairs, base, n_pairs..=n_pairs) {
+                    for digits in solution.expand() {
+                        assert_eq!(describe(&digits, base), digits);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_describe_trajectory() {
+        let trajectory = describe_trajectory(&[1], 10);
+        assert_eq!(trajectory.numbers[1], [1, 1]);
+        assert_eq!(trajectory.trajectory_len(), 12);
+        assert_eq!(trajectory.cycle_len(), 1);
+        assert_eq!(trajectory.cycle(), [parse_digits("32232114", 10).unwrap()]);
+        for n_pairs in 1..=6 {
+            for digits in brute_force(n_pairs, 10, Counts::SingleDigit) {
+                let distinct: BTreeSet<_> =
+                    digits.iter().skip(1).step_by(2).collect();
+                if distinct.len() != n_pairs {
+                    continue;
+                }
+                let trajectory = describe_trajectory(&digits, 10);
+                assert_eq!(trajectory.trajectory_len(), 0);
+                assert_eq!(trajectory.cycle_len(), 1);
+            }
+        }
+        // Fixed points reached from any start are self-descriptive numbers
+        // found by the search
+        let mut fixed_points = BTreeSet::new();
+        for start in 0..1000usize {
+            let digits = parse_digits(&start.to_string(), 10).unwrap();
+            if let [fixed_point] = describe_trajectory(&digits, 10).cycle() {
+                fixed_points.insert(fixed_point.clone());
+            }
+        }
+        for fixed_point in fixed_points {
+            let n_pairs = fixed_point.len() / 2;
+            assert!(
+                brute_force(n_pairs, 10, Counts::SingleDigit)
+                    .contains(&fixed_point)
+            );
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{
+    Convention, Counts, MAX_BASE, PairOrder, Solution, extreme::Extreme,
+};
+
+/// Pairs of a solution that share the same reps and descriptor.
+/// Permuting digits between such pairs does not produce a new number,
+/// so digits are chosen for the class as a whole.
+struct PairClass {
+    reps: u8,
+    descriptor: u8,
+    /// Number of times each allowed digit occurs as a descriptor
+    slot: u8,
+    /// Number of pairs in the class
+    len: usize,
+    /// Digits that may be described by pairs in the class, in descending
+    /// order
+    allowed: Vec<u8>,
+}
+
+/// Advance a sorted combination of indices into `0..n` to the next
+/// combination in lexicographic order
+pub(crate) fn next_combination(idxs: &mut [usize], n: usize) -> bool {
+    let k = idxs.len();
+    for idx in (0..k).rev() {
+        if idxs[idx] < n - k + idx {
+            idxs[idx] += 1;
+            for next_idx in idx + 1..k {
+                idxs[next_idx] = idxs[next_idx - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// The numerically smallest or largest concrete number represented by a
+/// solution with single-digit counts, with pairs written in `convention`,
+/// over every ordering of its pairs, without enumerating its expansions.
+/// The pairs of the largest number are in descending order of their first
+/// digit and then their second, and those of the smallest number are in
+/// ascending order, as with [`PairOrder`].
+pub fn extreme_expansion(
+    solution: &Solution,
+    extreme: Extreme,
+    convention: Convention,
+) -> Vec<u8> {
+    let classes = pair_classes(solution);
+    let (triples, order) = match (convention, extreme) {
+        (Convention::CountDigit, Extreme::Min) => {
+            (triples_by_count(classes, extreme), PairOrder::CountAsc)
+        }
+        (Convention::CountDigit, Extreme::Max) => {
+            (triples_by_count(classes, extreme), PairOrder::CountDesc)
+        }
+        (Convention::DigitCount, Extreme::Min) => (
+            triples_by_digit(&classes, solution.base, extreme),
+            PairOrder::DigitAsc,
+        ),
+        (Convention::DigitCount, Extreme::Max) => (
+            triples_by_digit(&classes, solution.base, extreme),
+            PairOrder::DigitDesc,
+        ),
+    };
+    order.apply(
+        &triples_pairs(triples),
+        solution.base,
+        solution.counts,
+        convention,
+    )
+}
+
+/// The `(reps, descriptor, digit)` triples of the smallest (or largest)
+/// number whose pairs are ordered by count.
+/// Every expansion has the same descriptors, so classes are visited in the
+/// order of their descriptors, and each takes the smallest (or largest)
+/// digits its slot has left.
+/// Pairs in the same descriptor block belong to distinct slots, which have
+/// disjoint allowed digits, so classes in a block never compete.
+fn triples_by_count(
+    mut classes: Vec<PairClass>,
+    extreme: Extreme,
+) -> Vec<(u8, u8, u8)> {
+    match extreme {
+        Extreme::Min => classes.sort_by_key(|class| class.descriptor),
+        Extreme::Max => {
+            classes.sort_by_key(|class| std::cmp::Reverse(class.descriptor))
+        }
+    }
+    let mut used = [false; MAX_BASE];
+    let mut triples = Vec::new();
+    for class in &classes {
+        let available: Vec<u8> = class
+            .allowed
+            .iter()
+            .copied()
+            .filter(|digit| !used[*digit as usize])
+            .collect();
+        let chosen = match extreme {
+            Extreme::Max => &available[..class.len],
+            Extreme::Min => &available[available.len() - class.len..],
+        };
+        for digit in chosen {
+            used[*digit as usize] = true;
+            triples.push((class.reps, class.descriptor, *digit));
+        }
+    }
+    triples
+}
+
+/// The `(reps, descriptor, digit)` triples of the smallest (or largest)
+/// number whose pairs are ordered by digit.
+/// Digits are visited from the smallest (or largest). A number that
+/// describes the digit beats one that does not, and one that describes it
+/// with a smaller (or larger) count beats one with any other count, so each
+/// digit is taken by the class with the smallest (or largest) descriptor
+/// that allows it and has room left.
+/// Classes in the same slot allow the same digits, so this never leaves a
+/// later digit without a class it needs.
+fn triples_by_digit(
+    classes: &[PairClass],
+    base: u8,
+    extreme: Extreme,
+) -> Vec<(u8, u8, u8)> {
+    let mut n_left: Vec<usize> =
+        classes.iter().map(|class| class.len).collect();
+    let digits: Vec<u8> = match extreme {
+        Extreme::Min => (0..base).collect(),
+        Extreme::Max => (0..base).rev().collect(),
+    };
+    let mut triples = Vec::new();
+    for digit in digits {
+        let candidates = (0..classes.len()).filter(|class_idx| {
+            n_left[*class_idx] != 0
+                && classes[*class_idx].allowed.contains(&digit)
+        });
+        let descriptor = |class_idx: &usize| classes[*class_idx].descriptor;
+        let best = match extreme {
+            Extreme::Min => candidates.min_by_key(descriptor),
+            Extreme::Max => candidates.max_by_key(descriptor),
+        };
+        if let Some(class_idx) = best {
+            n_left[class_idx] -= 1;
+            let class = &classes[class_idx];
+            triples.push((class.reps, class.descriptor, digit));
+        }
+    }
+    triples
+}
+
+/// Iterator over the concrete self-descriptive numbers represented by a
+/// [`Solution`].
+/// Each number is yielded as a sequence of digits, with pairs ordered by
+/// descriptor and then by described digit, both in descending order.
+pub struct Expansions {
+    base: u8,
+    counts: Counts,
+    convention: Convention,
+    classes: Vec<PairClass>,
+    /// Digits available to each class, given the digits chosen by earlier
+    /// classes
+    available: Vec<Vec<u8>>,
+    /// Indices into `available` of the digits chosen for each class
+    chosen: Vec<Vec<usize>>,
+    done: bool,
+}
+
+/// The (count, digit) pairs of `(reps, descriptor, digit)` triples, ordered
+/// by descriptor and then by described digit, both in descending order
+fn triples_pairs(mut triples: Vec<(u8, u8, u8)>) -> Vec<(u8, u8)> {
+    triples.sort_by_key(|(reps, descriptor, digit)| {
+        std::cmp::Reverse((*descriptor, *digit, *reps))
+    });
+    let mut res = Vec::new();
+    for (reps, descriptor, digit) in triples {
+        for _ in 0..reps {
+            res.push((descriptor, digit));
+        }
+    }
+    res
+}
+
+/// Render `(reps, descriptor, digit)` triples as digits in base `base`,
+/// with counts written as `counts` and pairs written in `convention`,
+/// ordered as in [`triples_pairs`]
+fn triples_digits(
+    triples: Vec<(u8, u8, u8)>,
+    base: u8,
+    counts: Counts,
+    convention: Convention,
+) -> Vec<u8> {
+    let mut res = Vec::new();
+    for (count, digit) in triples_pairs(triples) {
+        convention.write_pair(counts.digits(count, base), digit, &mut res);
+    }
+    res
+}
+
+/// Group the pairs of a solution into classes
+fn pair_classes(solution: &Solution) -> Vec<PairClass> {
+    // Number of times each digit occurs in descriptors
+    let mut descriptor_counts = [0usize; MAX_BASE];
+    for (reps, descriptor, _) in &solution.best_solution {
+        for digit in solution.counts.digits(*descriptor, solution.base) {
+            descriptor_counts[digit as usize] += *reps as usize;
+        }
+    }
+    let mut classes = Vec::<PairClass>::new();
+    for (reps, descriptor, _) in &solution.best_solution {
+        if let Some(class) = classes.iter_mut().find(|class| {
+            class.reps == *reps && class.descriptor == *descriptor
+        }) {
+            class.len += 1;
+            continue;
+        }
+        let slot = descriptor - reps;
+        let allowed = (0..solution.base)
+            .rev()
+            .filter(|digit| descriptor_counts[*digit as usize] == slot as usize)
+            .collect();
+        classes.push(PairClass {
+            reps: *reps,
+            descriptor: *descriptor,
+            slot,
+            len: 1,
+            allowed,
+        });
+    }
+    classes
+}
+
+/// Number of concrete numbers represented by a solution, without
+/// enumerating them.
+/// Classes in different slots have disjoint allowed digits, so each class
+/// chooses its digits from those left over by earlier classes in the same
+/// slot.
+pub fn count_expansions(solution: &Solution) -> u128 {
+    fn binomial(n: usize, k: usize) -> u128 {
+        (0..k).fold(1, |acc, idx| acc * (n - idx) as u128 / (idx + 1) as u128)
+    }
+    // Number of digits used by earlier classes in each slot
+    let mut n_used = [0usize; u8::MAX as usize + 1];
+    let mut res = 1;
+    for class in pair_classes(solution) {
+        let slot = class.slot as usize;
+        let n_available = class.allowed.len() - n_used[slot];
+        if n_available < class.len {
+            return 0;
+        }
+        res *= binomial(n_available, class.len);
+        n_used[slot] += class.len;
+    }
+    res
+}
+
+impl Expansions {
+    pub fn new(solution: &Solution) -> Self {
+        let classes = pair_classes(solution);
+        let n_classes = classes.len();
+        let mut res = Self {
+            base: solution.base,
+            counts: solution.counts,
+            convention: Convention::default(),
+            classes,
+            available: vec![Vec::new(); n_classes],
+            chosen: vec![Vec::new(); n_classes],
+            done: false,
+        };
+        if let Err(class_idx) = res.fill(0) {
+            res.done = class_idx == 0 || !res.advance(class_idx - 1);
+        }
+        res
+    }
+
+    /// Write the pairs of each number in `convention`
+    pub fn with_convention(mut self, convention: Convention) -> Self {
+        self.convention = convention;
+        self
+    }
+
+    /// Iterate over the (count, digit) pairs of each number, in the same
+    /// order as the digits that this iterator yields
+    pub fn pairs(mut self) -> impl Iterator<Item = Vec<(u8, u8)>> {
+        std::iter::from_fn(move || self.next_triples().map(triples_pairs))
+    }
+
+    /// The `(reps, descriptor, digit)` triples of the next number
+    fn next_triples(&mut self) -> Option<Vec<(u8, u8, u8)>> {
+        if self.done {
+            return None;
+        }
+        let mut triples = Vec::new();
+        for (class_idx, class) in self.classes.iter().enumerate() {
+            for idx in &self.chosen[class_idx] {
+                let digit = self.available[class_idx][*idx];
+                triples.push((class.reps, class.descriptor, digit));
+            }
+        }
+        self.done =
+            self.classes.is_empty() || !self.advance(self.classes.len() - 1);
+        Some(triples)
+    }
+
+    /// Choose the first available combination for each class, starting
+    /// with `start`.
+    /// Returns the index of the first class that could not be filled.
+    fn fill(&mut self, start: usize) -> Result<(), usize> {
+        let mut used = [false; MAX_BASE];
+        for class_idx in 0..start {
+            for idx in &self.chosen[class_idx] {
+                used[self.available[class_idx][*idx] as usize] = true;
+            }
+        }
+        for class_idx in start..self.classes.len() {
+            let class = &self.classes[class_idx];
+            let available: Vec<u8> = class
+                .allowed
+                .iter()
+                .copied()
+                .filter(|digit| !used[*digit as usize])
+                .collect();
+            if available.len() < class.len {
+                return Err(class_idx);
+            }
+            for digit in &available[..class.len] {
+                used[*digit as usize] = true;
+            }
+            self.available[class_idx] = available;
+            self.chosen[class_idx] = (0..class.len).collect();
+        }
+        Ok(())
+    }
+
+    /// Advance to the next combination for any class up to and including
+    /// `class_idx`, refilling later classes.
+    /// Returns `false` if no combinations remain.
+    fn advance(&mut self, mut class_idx: usize) -> bool {
+        loop {
+            let n_available = self.available[class_idx].len();
+            if next_combination(&mut self.chosen[class_idx], n_available) {
+                match self.fill(class_idx + 1) {
+                    Ok(()) => return true,
+                    Err(failed_idx) => class_idx = failed_idx - 1,
+                }
+            } else if class_idx == 0 {
+                return false;
+            } else {
+                class_idx -= 1;
+            }
+        }
+    }
+}
+
+impl Iterator for Expansions {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let triples = self.next_triples()?;
+        Some(triples_digits(
+            triples,
+            self.base,
+            self.counts,
+            self.convention,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{Convention, digits_string, solve_iter, verify_digits};
+
+    use super::count_expansions;
+
+    #[test]
+    fn test_expansions_self_descriptive() {
+        for base in [4, 10, 12] {
+            for n_pairs in 1..=10 {
+                for solution in solve_iter(n_pairs, base, ..) {
+                    let expansions: Vec<_> = solution.expand().collect();
+                    assert!(!expansions.is_empty());
+                    let unique: BTreeSet<_> = expansions.iter().collect();
+                    assert_eq!(unique.len(), expansions.len());
+                    for digits in &expansions {
+                        assert_eq!(digits.len(), n_pairs * 2);
+                        assert!(
+                            verify_digits(digits, Convention::CountDigit)
+                                .unwrap()
+                                .is_self_descriptive(),
+                            "{}",
+                            digits_string(digits)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_expansions() {
+        for base in [5, 10] {
+            for n_pairs in 1..=10 {
+                for solution in solve_iter(n_pairs, base, ..) {
+                    assert_eq!(
+                        count_expansions(&solution),
+                        solution.expand().count() as u128
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_expansions_free_digits() {
+        let numbers: BTreeSet<String> = solve_iter(12, 10, ..)
+            .filter(|solution| solution.best_solution.len() == 3)
+            .flat_map(|solution| solution.expand())
+            .map(|digits| digits_string(&digits))
+            .collect();
+        assert!(numbers.contains("999999969696686868686868"));
+        assert!(numbers.contains("999999969696606060606060"));
+        assert!(!numbers.contains("999999969696696969696969"));
+    }
+}
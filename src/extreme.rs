@@ -0,0 +1,189 @@
+use std::ops::RangeBounds;
+
+use crate::{Convention, Counts, expand::extreme_expansion, work_items};
+
+/// Which extreme of the concrete numbers to find
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Extreme {
+    Min,
+    Max,
+}
+
+impl Extreme {
+    /// Whether `lhs` is strictly closer to the extreme than `rhs`
+    fn better<T: Ord>(self, lhs: &T, rhs: &T) -> bool {
+        match self {
+            Self::Min => lhs < rhs,
+            Self::Max => lhs > rhs,
+        }
+    }
+}
+
+/// The numerically smallest or largest concrete self-descriptive number in
+/// base `base` with `n_pairs` pairs and a number of unique digits in
+/// `unique_digits`, with pairs written in `convention`, over every ordering
+/// of the pairs, or `None` if there are no solutions.
+/// The largest number has its pairs in descending order, and the smallest
+/// has them in ascending order, so neither need be an expansion.
+/// Counts are single digits, so all such numbers have the same number of
+/// digits. When counts are written first, every number from a work item
+/// starts with its largest (or smallest) descriptor, so work items whose
+/// leading descriptor is worse than the leading digit of the best number
+/// found so far are skipped without searching their pairings.
+pub fn extreme_number(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    extreme: Extreme,
+    convention: Convention,
+) -> Option<Vec<u8>> {
+    let mut best: Option<Vec<u8>> = None;
+    for work_item in
+        work_items(n_pairs, base, unique_digits, Counts::SingleDigit)
+    {
+        let leading = match extreme {
+            Extreme::Min => work_item.descriptors.first(),
+            Extreme::Max => work_item.descriptors.last(),
+        };
+        let Some(leading) = leading else {
+            continue;
+        };
+        if convention == Convention::CountDigit
+            && let Some(best) = &best
+            && extreme.better(&best[0], leading)
+        {
+            continue;
+        }
+        for solution in work_item.solve(base, Counts::SingleDigit) {
+            let digits = extreme_expansion(&solution, extreme, convention);
+            if best
+                .as_ref()
+                .is_none_or(|best| extreme.better(&digits, best))
+            {
+                best = Some(digits);
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Convention, expand::extreme_expansion, solve_iter, verify_digits,
+    };
+
+    use super::{Extreme, extreme_number};
+
+    /// Every self-descriptive number with `n_digits` digits in base `base`
+    /// whose pairs are written in `convention`, in ascending order, found by
+    /// checking every string of digits
+    fn every_number(
+        n_digits: usize,
+        base: u8,
+        convention: Convention,
+    ) -> Vec<Vec<u8>> {
+        let mut digits = vec![0; n_digits];
+        let mut res = Vec::new();
+        loop {
+            if verify_digits(&digits, convention)
+                .unwrap()
+                .is_self_descriptive()
+            {
+                res.push(digits.clone());
+            }
+            let Some(idx) = digits.iter().rposition(|digit| *digit + 1 < base)
+            else {
+                return res;
+            };
+            digits[idx] += 1;
+            digits[idx + 1..].fill(0);
+        }
+    }
+
+    #[test]
+    fn test_extreme_expansion() {
+        for convention in Convention::ALL {
+            for base in [5, 10] {
+                for n_pairs in 1..=10 {
+                    for solution in solve_iter(n_pairs, base, ..) {
+                        // The pairs of each expansion in ascending order
+                        let ascending: Vec<Vec<u8>> = solution
+                            .expand()
+                            .with_convention(convention)
+                            .map(|digits| {
+                                let mut pairs: Vec<&[u8]> =
+                                    digits.chunks_exact(2).collect();
+                                pairs.sort();
+                                pairs.concat()
+                            })
+                            .collect();
+                        let descending = ascending.iter().map(|digits| {
+                            let pairs: Vec<&[u8]> =
+                                digits.chunks_exact(2).rev().collect();
+                            pairs.concat()
+                        });
+                        assert_eq!(
+                            Some(&extreme_expansion(
+                                &solution,
+                                Extreme::Min,
+                                convention
+                            )),
+                            ascending.iter().min()
+                        );
+                        assert_eq!(
+                            Some(extreme_expansion(
+                                &solution,
+                                Extreme::Max,
+                                convention
+                            )),
+                            descending.max()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_number() {
+        for convention in Convention::ALL {
+            for (base, max_n_pairs) in [(3, 5), (4, 4), (10, 3)] {
+                for n_pairs in 1..=max_n_pairs {
+                    let numbers = every_number(n_pairs * 2, base, convention);
+                    let extreme_number = |extreme| {
+                        extreme_number(n_pairs, base, .., extreme, convention)
+                    };
+                    assert_eq!(
+                        extreme_number(Extreme::Min).as_ref(),
+                        numbers.first()
+                    );
+                    assert_eq!(
+                        extreme_number(Extreme::Max).as_ref(),
+                        numbers.last()
+                    );
+                }
+            }
+        }
+        let count_digit = Convention::CountDigit;
+        assert_eq!(
+            extreme_number(3, 10, .., Extreme::Min, count_digit),
+            Some(vec![2, 2, 4, 4, 4, 4])
+        );
+        assert_eq!(
+            extreme_number(4, 10, .., Extreme::Min, count_digit),
+            Some(vec![1, 0, 1, 2, 3, 1, 3, 3])
+        );
+        let digit_count = Convention::DigitCount;
+        assert_eq!(
+            extreme_number(3, 10, .., Extreme::Min, digit_count),
+            Some(vec![2, 2, 4, 4, 4, 4])
+        );
+        assert_eq!(
+            extreme_number(5, 10, .., Extreme::Max, digit_count),
+            Some(vec![9, 2, 9, 2, 4, 4, 2, 4, 2, 4])
+        );
+    }
+}
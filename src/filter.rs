@@ -0,0 +1,363 @@
+//! Filters that prune the search, and the pipeline that applies them.
+
+use std::sync::{Arc, LazyLock};
+
+use crate::{
+    Counts, Rejection, Solution, check_free_vars,
+    check_free_vars_rep_descriptor, check_reps_descriptor_counts,
+    check_reps_lte_descriptor,
+};
+
+/// Stage of the search that a filter is applied at
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stage {
+    /// Reps and descriptors, before pairing them
+    RepsDescriptors,
+    /// Each pairing of reps and descriptors
+    Pairing,
+}
+
+/// A constraint that rejects candidates that cannot be solutions.
+/// Each filter is applied at one [`Stage`], and only the check for that
+/// stage is called.
+pub trait Filter: Send + Sync {
+    /// Name of the filter in statistics and traces
+    fn name(&self) -> &str;
+
+    fn stage(&self) -> Stage;
+
+    /// Whether reps and descriptors, both in ascending order, may be
+    /// paired into a solution in base `base` with counts written as
+    /// `counts`
+    fn check_reps_descriptors(
+        &self,
+        _reps: &[u8],
+        _descriptors: &[u8],
+        _base: u8,
+        _counts: Counts,
+    ) -> bool {
+        true
+    }
+
+    /// Whether a pairing of reps and descriptors may be a solution in base
+    /// `base` with counts written as `counts`
+    fn check_pairing(
+        &self,
+        _rep_descriptors: &[(u8, u8)],
+        _base: u8,
+        _counts: Counts,
+    ) -> bool {
+        true
+    }
+}
+
+impl Filter for Rejection {
+    fn name(&self) -> &str {
+        match self {
+            Self::RepsGtDescriptors => "reps > descriptors",
+            Self::FreeVars => "check_free_vars",
+            Self::RepsLteDescriptor => "check_reps_lte_descriptor",
+            Self::FreeVarsRepDescriptor => "check_free_vars_rep_descriptor",
+            Self::RepsDescriptorCounts => "check_reps_descriptor_counts",
+        }
+    }
+
+    fn stage(&self) -> Stage {
+        match self {
+            Self::RepsGtDescriptors | Self::FreeVars => Stage::RepsDescriptors,
+            _ => Stage::Pairing,
+        }
+    }
+
+    fn check_reps_descriptors(
+        &self,
+        reps: &[u8],
+        descriptors: &[u8],
+        base: u8,
+        counts: Counts,
+    ) -> bool {
+        match self {
+            Self::RepsGtDescriptors => reps <= descriptors,
+            Self::FreeVars => check_free_vars(reps, descriptors, base, counts),
+            _ => true,
+        }
+    }
+
+    fn check_pairing(
+        &self,
+        rep_descriptors: &[(u8, u8)],
+        base: u8,
+        counts: Counts,
+    ) -> bool {
+        match self {
+            Self::RepsLteDescriptor => {
+                check_reps_lte_descriptor(rep_descriptors)
+            }
+            Self::FreeVarsRepDescriptor => {
+                check_free_vars_rep_descriptor(rep_descriptors, base, counts)
+            }
+            Self::RepsDescriptorCounts => {
+                check_reps_descriptor_counts(rep_descriptors, base, counts)
+                    .is_some()
+            }
+            _ => true,
+        }
+    }
+}
+
+/// The default pipeline, shared by searches without extra filters
+pub(crate) static DEFAULT_PIPELINE: LazyLock<Pipeline> =
+    LazyLock::new(Pipeline::default);
+
+/// Filters applied by a search, in order within each stage.
+/// Every pipeline starts with the built-in filters of [`Rejection::ALL`],
+/// at the index of each [`Rejection`], since solutions are built from
+/// pairings that pass them and only from such pairings. They cannot be
+/// removed or reordered, and extra filters are only appended after them,
+/// so extra filters are applied after the built-in filters of the same
+/// stage.
+#[derive(Clone)]
+pub struct Pipeline {
+    filters: Vec<Arc<dyn Filter>>,
+    /// Indices of the filters at each stage
+    reps_descriptors: Vec<usize>,
+    pairing: Vec<usize>,
+}
+
+impl Default for Pipeline {
+    /// The built-in filters, which reproduce the search without any extra
+    /// filters
+    fn default() -> Self {
+        let mut res = Self {
+            filters: Vec::new(),
+            reps_descriptors: Vec::new(),
+            pairing: Vec::new(),
+        };
+        for rejection in Rejection::ALL {
+            res = res.with_extra_filter(rejection);
+        }
+        res
+    }
+}
+
+impl Pipeline {
+    /// Append a filter, after the filters already at its stage
+    pub fn with_extra_filter(mut self, filter: impl Filter + 'static) -> Self {
+        let filter_idx = self.filters.len();
+        match filter.stage() {
+            Stage::RepsDescriptors => self.reps_descriptors.push(filter_idx),
+            Stage::Pairing => self.pairing.push(filter_idx),
+        }
+        self.filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Every filter, in the order they are applied within each stage
+    pub fn filters(&self) -> impl Iterator<Item = &dyn Filter> {
+        self.filters.iter().map(|filter| filter.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn get(&self, filter_idx: usize) -> &dyn Filter {
+        self.filters[filter_idx].as_ref()
+    }
+
+    /// Apply the filters on reps and descriptors, returning the index of
+    /// the first filter that rejects them
+    pub fn filter_reps_descriptors(
+        &self,
+        reps: &[u8],
+        descriptors: &[u8],
+        base: u8,
+        counts: Counts,
+    ) -> Result<(), usize> {
+        for filter_idx in &self.reps_descriptors {
+            let filter = &self.filters[*filter_idx];
+            if !filter.check_reps_descriptors(reps, descriptors, base, counts) {
+                return Err(*filter_idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the filters on a pairing, returning the solution if the
+    /// pairing passes all of them, or the index of the first filter that
+    /// rejects it.
+    /// The built-in filter on descriptor counts builds the solution, so it
+    /// is built once for each pairing that reaches it.
+    pub fn filter_pairing(
+        &self,
+        rep_descriptors: &[(u8, u8)],
+        base: u8,
+        counts: Counts,
+    ) -> Result<Solution, usize> {
+        let mut solution = None;
+        for filter_idx in &self.pairing {
+            if *filter_idx == Rejection::RepsDescriptorCounts as usize {
+                solution =
+                    check_reps_descriptor_counts(rep_descriptors, base, counts);
+                if solution.is_none() {
+                    return Err(*filter_idx);
+                }
+                continue;
+            }
+            let filter = &self.filters[*filter_idx];
+            if !filter.check_pairing(rep_descriptors, base, counts) {
+                return Err(*filter_idx);
+            }
+        }
+        Ok(solution.expect("every pipeline has the built-in filters"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Counts, Rejection, Shard, Solution, SolveIter, filter_pairing,
+        filter_reps_descriptors, work_items,
+    };
+
+    use super::{Filter, Pipeline, Stage};
+
+    /// Rejects pairings where any digit is described by more than two pairs
+    struct AtMostTwoReps;
+
+    impl Filter for AtMostTwoReps {
+        fn name(&self) -> &str {
+            "at most two reps"
+        }
+
+        fn stage(&self) -> Stage {
+            Stage::Pairing
+        }
+
+        fn check_pairing(
+            &self,
+            rep_descriptors: &[(u8, u8)],
+            _base: u8,
+            _counts: Counts,
+        ) -> bool {
+            rep_descriptors.iter().all(|(reps, _)| *reps <= 2)
+        }
+    }
+
+    /// Rejects descriptors greater than 5
+    struct AtMostFive;
+
+    impl Filter for AtMostFive {
+        fn name(&self) -> &str {
+            "at most five"
+        }
+
+        fn stage(&self) -> Stage {
+            Stage::RepsDescriptors
+        }
+
+        fn check_reps_descriptors(
+            &self,
+            _reps: &[u8],
+            descriptors: &[u8],
+            _base: u8,
+            _counts: Counts,
+        ) -> bool {
+            descriptors.iter().all(|descriptor| *descriptor <= 5)
+        }
+    }
+
+    #[test]
+    fn test_default_pipeline() {
+        let pipeline = Pipeline::default();
+        for (filter_idx, rejection) in Rejection::ALL.into_iter().enumerate() {
+            assert_eq!(rejection as usize, filter_idx);
+            assert_eq!(pipeline.get(filter_idx).name(), rejection.name());
+        }
+        for work_item in work_items(8, 10, .., Counts::SingleDigit) {
+            let (reps, descriptors) = (&work_item.reps, &work_item.descriptors);
+            assert_eq!(
+                pipeline
+                    .filter_reps_descriptors(
+                        reps,
+                        descriptors,
+                        10,
+                        Counts::SingleDigit
+                    )
+                    .map_err(|filter_idx| Rejection::ALL[filter_idx]),
+                filter_reps_descriptors(
+                    reps,
+                    descriptors,
+                    10,
+                    Counts::SingleDigit
+                )
+            );
+        }
+        let pairings = [
+            vec![(1, 2), (2, 1)],
+            vec![(3, 4), (1, 2)],
+            vec![(2, 4), (1, 3), (1, 1)],
+        ];
+        for rep_descriptors in pairings {
+            let expected =
+                filter_pairing(&rep_descriptors, 10, Counts::SingleDigit)
+                    .map(|solution| solution.to_string());
+            let actual = pipeline
+                .filter_pairing(&rep_descriptors, 10, Counts::SingleDigit)
+                .map(|solution| solution.to_string())
+                .map_err(|filter_idx| Rejection::ALL[filter_idx]);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_filters() {
+        let pipeline = Pipeline::default()
+            .with_extra_filter(AtMostTwoReps)
+            .with_extra_filter(AtMostFive);
+        let mut n_rejected = 0;
+        for n_pairs in 1..=10 {
+            let mut solutions = SolveIter::new_shard(
+                n_pairs,
+                10,
+                ..,
+                Shard::default(),
+                Counts::SingleDigit,
+            )
+            .with_pipeline(pipeline.clone());
+            let filtered: Vec<String> = solutions
+                .by_ref()
+                .map(|solution| solution.to_string())
+                .collect();
+            let all: Vec<Solution> = SolveIter::new(n_pairs, 10, ..).collect();
+            let expected: Vec<String> = all
+                .iter()
+                .filter(|solution| {
+                    solution.best_solution.iter().all(
+                        |(reps, descriptor, _)| *reps <= 2 && *descriptor <= 5,
+                    )
+                })
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(filtered, expected);
+            n_rejected += all.len() - filtered.len();
+            let stats = solutions.stats();
+            let names: Vec<&str> = stats
+                .funnel(solutions.pipeline())
+                .map(|(filter, _, _)| filter.name())
+                .collect();
+            assert_eq!(names[5..], ["at most two reps", "at most five"]);
+            let (_, n_in, n_last_rejected) = stats
+                .funnel(solutions.pipeline())
+                .filter(|(filter, _, _)| filter.stage() == Stage::Pairing)
+                .last()
+                .unwrap();
+            assert_eq!(n_in - n_last_rejected, filtered.len() as u64);
+        }
+        assert!(n_rejected > 0);
+    }
+}
@@ -0,0 +1,434 @@
+//! Self-descriptive numbers as an integer linear program, for external ILP
+//! solvers, and a small branch and bound solver for checking the model.
+
+use std::fmt::Write as _;
+
+/// Comparison between the terms of a constraint and its right-hand side
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sense {
+    Le,
+    Eq,
+    Ge,
+}
+
+/// An integer variable from 0 to `upper`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Var {
+    pub name: String,
+    pub upper: i64,
+}
+
+/// A linear constraint on the variables of a model
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constraint {
+    pub name: String,
+    /// Coefficient of each variable, by its index
+    pub terms: Vec<(usize, i64)>,
+    pub sense: Sense,
+    pub rhs: i64,
+}
+
+/// An integer linear feasibility problem, with no objective
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Model {
+    pub name: String,
+    pub vars: Vec<Var>,
+    pub constraints: Vec<Constraint>,
+}
+
+impl Model {
+    pub fn add_var(&mut self, name: String, upper: i64) -> usize {
+        self.vars.push(Var { name, upper });
+        self.vars.len() - 1
+    }
+
+    /// Add a constraint, combining the terms of each variable, since
+    /// formats such as MPS only allow one coefficient per variable
+    pub fn add_constraint(
+        &mut self,
+        name: String,
+        terms: impl IntoIterator<Item = (usize, i64)>,
+        sense: Sense,
+        rhs: i64,
+    ) {
+        let mut combined: Vec<(usize, i64)> = Vec::new();
+        for (var, coef) in terms {
+            match combined.iter_mut().find(|(other, _)| *other == var) {
+                Some((_, other_coef)) => *other_coef += coef,
+                None => combined.push((var, coef)),
+            }
+        }
+        combined.retain(|(_, coef)| *coef != 0);
+        self.constraints.push(Constraint {
+            name,
+            terms: combined,
+            sense,
+            rhs,
+        });
+    }
+
+    /// The model in the CPLEX LP format
+    pub fn lp(&self) -> Lp<'_> {
+        Lp(self)
+    }
+
+    /// The model in the free MPS format
+    pub fn mps(&self) -> Mps<'_> {
+        Mps(self)
+    }
+
+    /// Iterate over every integer solution of the model, as the value of
+    /// each variable by its index.
+    /// Bounds are tightened by each constraint until none changes, and
+    /// the variable with the fewest remaining values is branched on.
+    pub fn solutions(&self) -> impl Iterator<Item = Vec<i64>> + '_ {
+        let bounds: Vec<(i64, i64)> =
+            self.vars.iter().map(|var| (0, var.upper)).collect();
+        let mut stack = vec![bounds];
+        std::iter::from_fn(move || {
+            while let Some(mut bounds) = stack.pop() {
+                if !self.propagate(&mut bounds) {
+                    continue;
+                }
+                let branch = (0..bounds.len())
+                    .filter(|idx| bounds[*idx].0 < bounds[*idx].1)
+                    .min_by_key(|idx| bounds[*idx].1 - bounds[*idx].0);
+                let Some(var) = branch else {
+                    return Some(
+                        bounds.into_iter().map(|(lo, _)| lo).collect(),
+                    );
+                };
+                let (lo, hi) = bounds[var];
+                for value in (lo..=hi).rev() {
+                    let mut branch = bounds.clone();
+                    branch[var] = (value, value);
+                    stack.push(branch);
+                }
+            }
+            None
+        })
+    }
+
+    /// Tighten the bounds of the variables until no constraint changes
+    /// them, returning false if a constraint cannot be satisfied
+    fn propagate(&self, bounds: &mut [(i64, i64)]) -> bool {
+        loop {
+            let mut changed = false;
+            for constraint in &self.constraints {
+                let signs: &[i64] = match constraint.sense {
+                    Sense::Le => &[1],
+                    Sense::Ge => &[-1],
+                    Sense::Eq => &[1, -1],
+                };
+                for sign in signs {
+                    match tighten(constraint, *sign, bounds) {
+                        None => return false,
+                        Some(tightened) => changed |= tightened,
+                    }
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+}
+
+/// Tighten the bounds of the variables of `sign` times the constraint, as
+/// a `<=` constraint, returning whether any bound changed, or `None` if
+/// the constraint cannot be satisfied
+fn tighten(
+    constraint: &Constraint,
+    sign: i64,
+    bounds: &mut [(i64, i64)],
+) -> Option<bool> {
+    let min_activity: i64 = constraint
+        .terms
+        .iter()
+        .map(|(var, coef)| {
+            let (lo, hi) = bounds[*var];
+            let coef = coef * sign;
+            coef * if coef > 0 { lo } else { hi }
+        })
+        .sum();
+    let slack = constraint.rhs * sign - min_activity;
+    if slack < 0 {
+        return None;
+    }
+    let mut changed = false;
+    for (var, coef) in &constraint.terms {
+        let coef = coef * sign;
+        let (lo, hi) = &mut bounds[*var];
+        if coef > 0 && *lo + slack / coef < *hi {
+            *hi = *lo + slack / coef;
+            changed = true;
+        } else if coef < 0 && *hi - slack / -coef > *lo {
+            *lo = *hi - slack / -coef;
+            changed = true;
+        }
+    }
+    Some(changed)
+}
+
+/// Terms of a constraint, starting a new line after every few terms
+fn terms_string(model: &Model, terms: &[(usize, i64)]) -> String {
+    let mut res = String::new();
+    for (idx, (var, coef)) in terms.iter().enumerate() {
+        if idx != 0 && idx % 8 == 0 {
+            res.push_str("\n  ");
+        }
+        let sign = if *coef < 0 { '-' } else { '+' };
+        if idx != 0 || *coef < 0 {
+            write!(res, "{sign} ").unwrap();
+        }
+        if coef.abs() != 1 {
+            write!(res, "{} ", coef.abs()).unwrap();
+        }
+        write!(res, "{} ", model.vars[*var].name).unwrap();
+    }
+    res
+}
+
+/// A model written in the CPLEX LP format, from [`Model::lp`]
+pub struct Lp<'a>(&'a Model);
+
+impl std::fmt::Display for Lp<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let model = self.0;
+        writeln!(f, "\\ {}", model.name)?;
+        writeln!(f, "Minimize")?;
+        if let Some(var) = model.vars.first() {
+            // Some readers need an objective with a term
+            writeln!(f, " obj: 0 {}", var.name)?;
+        }
+        writeln!(f, "Subject To")?;
+        for constraint in &model.constraints {
+            let sense = match constraint.sense {
+                Sense::Le => "<=",
+                Sense::Eq => "=",
+                Sense::Ge => ">=",
+            };
+            writeln!(
+                f,
+                " {}: {}{sense} {}",
+                constraint.name,
+                terms_string(model, &constraint.terms),
+                constraint.rhs
+            )?;
+        }
+        writeln!(f, "Bounds")?;
+        for var in &model.vars {
+            writeln!(f, " 0 <= {} <= {}", var.name, var.upper)?;
+        }
+        writeln!(f, "General")?;
+        for var in &model.vars {
+            writeln!(f, " {}", var.name)?;
+        }
+        writeln!(f, "End")
+    }
+}
+
+/// A model written in the free MPS format, from [`Model::mps`]
+pub struct Mps<'a>(&'a Model);
+
+impl std::fmt::Display for Mps<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let model = self.0;
+        writeln!(f, "* {}", model.name)?;
+        writeln!(f, "NAME")?;
+        writeln!(f, "ROWS")?;
+        writeln!(f, " N obj")?;
+        for constraint in &model.constraints {
+            let sense = match constraint.sense {
+                Sense::Le => 'L',
+                Sense::Eq => 'E',
+                Sense::Ge => 'G',
+            };
+            writeln!(f, " {sense} {}", constraint.name)?;
+        }
+        // Coefficients are listed by variable
+        let mut columns = vec![Vec::new(); model.vars.len()];
+        for constraint in &model.constraints {
+            for (var, coef) in &constraint.terms {
+                columns[*var].push((&constraint.name, *coef));
+            }
+        }
+        writeln!(f, "COLUMNS")?;
+        writeln!(f, " MARKER 'MARKER' 'INTORG'")?;
+        for (var, column) in model.vars.iter().zip(&columns) {
+            for (row, coef) in column {
+                writeln!(f, " {} {row} {coef}", var.name)?;
+            }
+        }
+        writeln!(f, " MARKER 'MARKER' 'INTEND'")?;
+        writeln!(f, "RHS")?;
+        for constraint in &model.constraints {
+            if constraint.rhs != 0 {
+                writeln!(f, " RHS {} {}", constraint.name, constraint.rhs)?;
+            }
+        }
+        writeln!(f, "BOUNDS")?;
+        for var in &model.vars {
+            writeln!(f, " UP BND {} {}", var.name, var.upper)?;
+        }
+        writeln!(f, "ENDATA")
+    }
+}
+
+/// The self-descriptive numbers in base `base` with `n_pairs` pairs, with
+/// counts written as single digits.
+/// For each count `c` and digit `d`, `x_c_d` is the number of pairs
+/// `(c, d)`, and `y_c_d` is 1 if there are any. Each digit is described by
+/// at most one count, which is the number of times the digit occurs, either
+/// as a described digit or as a count.
+pub fn self_descriptive_model(n_pairs: usize, base: u8) -> Model {
+    let n_pairs_i64 = n_pairs as i64;
+    let mut model = Model {
+        name: format!(
+            "self-descriptive numbers with {n_pairs} pairs in base {base}"
+        ),
+        ..Model::default()
+    };
+    let mut x = vec![Vec::new(); base as usize];
+    let mut y = vec![Vec::new(); base as usize];
+    for count in 1..base {
+        for digit in 0..base {
+            y[count as usize]
+                .push(model.add_var(format!("y_{count}_{digit}"), 1));
+            x[count as usize]
+                .push(model.add_var(format!("x_{count}_{digit}"), n_pairs_i64));
+        }
+    }
+    let pairs = x.iter().flatten().map(|var| (*var, 1));
+    model.add_constraint("pairs".to_string(), pairs, Sense::Eq, n_pairs_i64);
+    for count in 1..base as usize {
+        for digit in 0..base as usize {
+            let (x, y) = (x[count][digit], y[count][digit]);
+            model.add_constraint(
+                format!("some_{count}_{digit}"),
+                vec![(x, 1), (y, -1)],
+                Sense::Ge,
+                0,
+            );
+            model.add_constraint(
+                format!("none_{count}_{digit}"),
+                vec![(x, 1), (y, -n_pairs_i64)],
+                Sense::Le,
+                0,
+            );
+        }
+    }
+    for digit in 0..base as usize {
+        let described = (1..base as usize).map(|count| (y[count][digit], 1));
+        model.add_constraint(
+            format!("described_{digit}"),
+            described,
+            Sense::Le,
+            1,
+        );
+        // Occurrences as a described digit and as a count, minus the count
+        // that describes the digit
+        let mut occurrences: Vec<(usize, i64)> = (1..base as usize)
+            .map(|count| (x[count][digit], 1))
+            .collect();
+        occurrences.extend(x[digit].iter().map(|var| (*var, 1)));
+        occurrences.extend(
+            (1..base as usize).map(|count| (y[count][digit], -(count as i64))),
+        );
+        model.add_constraint(
+            format!("count_{digit}"),
+            occurrences,
+            Sense::Eq,
+            0,
+        );
+    }
+    model
+}
+
+/// The digits of the number of a solution of [`self_descriptive_model`],
+/// with pairs ordered by count and then by digit, both in descending order,
+/// matching [`crate::Solution::expand`]
+pub fn model_digits(solution: &[i64], base: u8) -> Vec<u8> {
+    let mut res = Vec::new();
+    for count in (1..base).rev() {
+        for digit in (0..base).rev() {
+            // Variables alternate between y and x, by count and then by
+            // digit
+            let pair_idx =
+                (count as usize - 1) * base as usize + digit as usize;
+            for _ in 0..solution[pair_idx * 2 + 1] {
+                res.extend([count, digit]);
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::solve_iter;
+
+    use super::{Model, Sense, model_digits, self_descriptive_model};
+
+    #[test]
+    fn test_solutions() {
+        // x + 2y = 4, x >= 1
+        let mut model = Model::default();
+        let x = model.add_var("x".to_string(), 4);
+        let y = model.add_var("y".to_string(), 4);
+        model.add_constraint(
+            "sum".to_string(),
+            vec![(x, 1), (y, 2)],
+            Sense::Eq,
+            4,
+        );
+        model.add_constraint("x".to_string(), vec![(x, 1)], Sense::Ge, 1);
+        let solutions: BTreeSet<Vec<i64>> = model.solutions().collect();
+        assert_eq!(solutions, BTreeSet::from([vec![2, 1], vec![4, 0]]));
+        model.add_constraint("odd".to_string(), vec![(x, 2)], Sense::Eq, 3);
+        assert_eq!(model.solutions().count(), 0);
+    }
+
+    #[test]
+    fn test_self_descriptive_model() {
+        for (base, max_n_pairs) in [(2, 3), (3, 4), (4, 5), (5, 5), (10, 4)] {
+            for n_pairs in 1..=max_n_pairs {
+                let model = self_descriptive_model(n_pairs, base);
+                let numbers: Vec<Vec<u8>> = model
+                    .solutions()
+                    .map(|solution| model_digits(&solution, base))
+                    .collect();
+                let expected: BTreeSet<Vec<u8>> = solve_iter(n_pairs, base, ..)
+                    .flat_map(|solution| solution.expand())
+                    .collect();
+                assert_eq!(numbers.len(), expected.len());
+                assert_eq!(
+                    numbers.into_iter().collect::<BTreeSet<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lp_mps() {
+        let model = self_descriptive_model(1, 3);
+        let lp = model.lp().to_string();
+        assert!(lp.contains(
+            " pairs: x_1_0 + x_1_1 + x_1_2 + x_2_0 + x_2_1 + x_2_2 = 1\n"
+        ));
+        assert!(lp.contains(" none_2_1: x_2_1 - y_2_1 <= 0\n"));
+        assert!(lp.contains(
+            " count_2: x_1_2 + 2 x_2_2 + x_2_0 + x_2_1 - y_1_2 - 2 y_2_2 = 0\n"
+        ));
+        assert!(lp.ends_with("General\n y_1_0\n x_1_0\n y_1_1\n x_1_1\n y_1_2\n x_1_2\n y_2_0\n x_2_0\n y_2_1\n x_2_1\n y_2_2\n x_2_2\nEnd\n"));
+        let mps = model.mps().to_string();
+        assert!(mps.contains(" E pairs\n G some_1_0\n L none_1_0\n"));
+        assert!(mps.contains(" x_2_2 pairs 1\n"));
+        assert!(mps.contains(" x_2_2 count_2 2\n"));
+        assert!(mps.contains("RHS\n RHS pairs 1\n RHS described_0 1\n"));
+        assert!(mps.ends_with(" UP BND x_2_2 1\nENDATA\n"));
+    }
+}
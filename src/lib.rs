@@ -0,0 +1,793 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, btree_map},
+    fmt::Write,
+    ops::{Bound, RangeBounds, RangeInclusive},
+};
+
+use lender::Lender;
+
+pub mod autobiographical;
+pub mod brute_force;
+pub mod cnf;
+pub mod convention;
+pub mod count;
+pub mod counts;
+pub mod cycles;
+pub mod describe;
+pub mod expand;
+pub mod extreme;
+pub mod filter;
+pub mod ilp;
+pub mod order;
+pub mod ordered_pairings;
+pub mod parallel;
+pub mod partition_parts;
+pub mod search;
+pub mod stats;
+pub mod verify;
+
+pub use autobiographical::{
+    WrongPosition, solve_autobiographical, verify_autobiographical,
+};
+pub use brute_force::brute_force;
+pub use cnf::{Cnf, cnf_digits, self_descriptive_cnf};
+pub use convention::Convention;
+pub use count::{PairsCount, UniqueDigitsCount, count_solutions};
+pub use counts::{
+    BaseCounts, CountDigits, CountRenderer, Counts, MultiDigitCounts,
+    SingleDigitCounts, WordCounts, count_digits, count_string, count_word,
+    counts_string, letter_char,
+};
+pub use cycles::{Cycle, solve_cycles};
+pub use describe::{Trajectory, describe, describe_trajectory};
+pub use expand::Expansions;
+pub use extreme::{Extreme, extreme_number};
+pub use filter::{Filter, Pipeline, Stage};
+pub use ilp::{Model, model_digits, self_descriptive_model};
+pub use order::{PairOrder, count_orderings};
+pub use ordered_pairings::OrderedPairings;
+pub use parallel::{ParSolveIter, solve_par_iter, solve_shard_par_iter};
+pub use partition_parts::PartitionsParts;
+pub use search::{Checkpoint, Progress, SolveIter};
+pub use stats::{Rejection, Stats};
+pub use verify::{Verification, verify, verify_digits};
+
+/// Largest supported numeral base
+pub const MAX_BASE: usize = 36;
+
+/// Render a digit, using uppercase letters for digits above 9
+pub fn digit_char(digit: u8) -> char {
+    char::from_digit(digit as u32, MAX_BASE as u32)
+        .unwrap()
+        .to_ascii_uppercase()
+}
+
+/// Identifier for the `idx`'th var slot in a rendered solution
+pub fn var_ident(idx: u8) -> char {
+    const LOWERCASE_A_ASCII: u8 = 0x61;
+    if idx < 8 {
+        char::from(LOWERCASE_A_ASCII + idx)
+    } else {
+        // Avoid using `i` as an ident
+        char::from(LOWERCASE_A_ASCII + idx + 1)
+    }
+}
+
+/// Render a sequence of digits as a number
+pub fn digits_string(digits: &[u8]) -> String {
+    digits.iter().copied().map(digit_char).collect()
+}
+
+/// Number of distinct digits in the descriptors, written as `counts` in
+/// base `base`.
+/// Each of these digits must be described by exactly one unique pair.
+fn n_descriptor_digits(descriptors: &[u8], base: u8, counts: Counts) -> usize {
+    let mut is_descriptor_digit = [false; MAX_BASE];
+    let mut res = 0;
+    for descriptor in descriptors {
+        for digit in counts.digits(*descriptor, base) {
+            if !std::mem::replace(
+                &mut is_descriptor_digit[digit as usize],
+                true,
+            ) {
+                res += 1;
+            }
+        }
+    }
+    res
+}
+
+/// If n_unique_descriptors < n_unique_digits, then
+/// (n_unique_digits - n_unique_descriptors) values from reps must appear
+/// in descriptors.
+/// If there are n_ones 1s in the descriptors, then
+/// n_unique_descriptors + n_ones - 1 must be less than or equal to n_unique_digits.
+/// Descriptors may be written with several digits, so
+/// n_unique_descriptors counts the distinct digits of the descriptors.
+pub fn check_free_vars(
+    reps: &[u8],
+    descriptors: &[u8],
+    base: u8,
+    counts: Counts,
+) -> bool {
+    let n_unique_digits = descriptors.len();
+    let n_unique_descriptors = n_descriptor_digits(descriptors, base, counts);
+    let n_ones = descriptors.iter().take_while(|d| **d == 1).count();
+    if n_unique_descriptors > n_unique_digits
+        || (n_ones != 0 && n_unique_descriptors + n_ones - 1 > n_unique_digits)
+    {
+        false
+    } else if n_unique_descriptors == n_unique_digits {
+        true
+    } else {
+        let mut reps_required_in_descriptors =
+            n_unique_digits - n_unique_descriptors;
+        let mut reps = reps.iter();
+        while reps_required_in_descriptors > 0 {
+            let Some(rep) = reps.next() else { return false };
+            if descriptors.iter().any(|desc| rep == desc) {
+                reps_required_in_descriptors -= 1;
+            }
+        }
+        true
+    }
+}
+
+/// Each descriptor's reps must be less than or equal to the descriptor.
+/// For the descriptor 9, the greatest possible number of reps is 7.
+pub fn check_reps_lte_descriptor(rep_descriptors: &[(u8, u8)]) -> bool {
+    rep_descriptors.iter().all(
+        |(reps, descriptor)| reps <= descriptor, /* && (*descriptor != 9 || *reps < 7) */
+    )
+}
+
+/// If a rep is equal to a descriptor, then we need a free var
+pub fn check_free_vars_rep_descriptor(
+    rep_descriptors: &[(u8, u8)],
+    base: u8,
+    counts: Counts,
+) -> bool {
+    let n_unique_digits = rep_descriptors.len();
+    let mut n_unique_descriptors = 0usize;
+    // i'th element indicates if the digit i occurs in descriptors
+    let mut descriptor_used = [false; MAX_BASE];
+    let mut free_vars_needed = 0usize;
+    for (rep, d) in rep_descriptors {
+        for digit in counts.digits(*d, base) {
+            if !std::mem::replace(&mut descriptor_used[digit as usize], true) {
+                n_unique_descriptors += 1;
+            }
+        }
+        if rep == d {
+            free_vars_needed += 1;
+        }
+    }
+    let free_vars = n_unique_digits.checked_sub(n_unique_descriptors);
+    free_vars == Some(free_vars_needed)
+}
+
+/// The digit described by a pair
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Described {
+    Digit(u8),
+    /// Index of the var set
+    Var(u8),
+}
+
+/// A family of self-descriptive numbers sharing the same
+/// `(reps, descriptor, described)` triples
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Solution {
+    /// `(reps, descriptor, described)` triples, using the best digit for
+    /// each var slot
+    pub best_solution: Vec<(u8, u8, Described)>,
+    /// Possible digits for each var slot, keyed by the var index
+    pub vars: BTreeMap<u8, BTreeSet<u8>>,
+    /// Numeral base of the represented numbers
+    pub base: u8,
+    /// How the counts of the represented numbers are written.
+    /// Counts written as digits in `base` are rendered the same whether or
+    /// not they may have several digits, so only other ways of writing
+    /// counts are serialized.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Counts::is_digits")
+    )]
+    pub counts: Counts,
+}
+
+impl Solution {
+    pub fn new(rep_descriptors: &[(u8, u8)], base: u8, counts: Counts) -> Self {
+        // Number of times each digit occurs in descriptors
+        let mut unique_descriptor_counts = BTreeMap::new();
+        // possible slots for a digit that occurs k times in descriptors
+        let mut slots_to_rep_descriptors =
+            HashMap::<u8, Vec<_>>::with_capacity(rep_descriptors.len());
+        let mut descriptor_to_reps = BTreeMap::<u8, Vec<u8>>::new();
+        for (reps, descriptor) in rep_descriptors {
+            for digit in counts.digits(*descriptor, base) {
+                unique_descriptor_counts
+                    .entry(digit)
+                    .and_modify(|count| *count += *reps)
+                    .or_insert(*reps);
+            }
+            slots_to_rep_descriptors
+                .entry(*descriptor - *reps)
+                .or_default()
+                .push((reps, descriptor));
+            descriptor_to_reps
+                .entry(*descriptor)
+                .or_default()
+                .push(*reps);
+        }
+        for rep_descriptors in slots_to_rep_descriptors.values_mut() {
+            rep_descriptors
+                .sort_by_key(|(reps, descriptor)| (*descriptor, *reps));
+        }
+        for reps in descriptor_to_reps.values_mut() {
+            reps.sort();
+        }
+        let n_unique_descriptors = unique_descriptor_counts.len();
+        let n_free_vars = rep_descriptors.len() - n_unique_descriptors;
+        let mut slots_to_vars = BTreeMap::<u8, BTreeSet<u8>>::new();
+        if n_free_vars != 0 {
+            let possible_free_vars: BTreeSet<u8> = (0..base)
+                .filter(|d| !unique_descriptor_counts.contains_key(d))
+                .collect();
+            slots_to_vars.insert(0, possible_free_vars);
+        }
+        for (descriptor, count) in unique_descriptor_counts {
+            slots_to_vars.entry(count).or_default().insert(descriptor);
+        }
+        let mut res = Self {
+            best_solution: Vec::with_capacity(rep_descriptors.len()),
+            vars: {
+                let slots_to_unique_rep_descriptor_counts: HashMap<u8, usize> =
+                    slots_to_rep_descriptors
+                        .iter()
+                        .map(|(slot, rds)| {
+                            let mut rds = rds.clone();
+                            rds.dedup();
+                            (*slot, rds.len())
+                        })
+                        .collect();
+                let mut vars = slots_to_vars.clone();
+                vars.retain(|slot, _vs| {
+                    slots_to_unique_rep_descriptor_counts[slot] > 1
+                });
+                vars
+            },
+            base,
+            counts,
+        };
+        for (descriptor, reps) in descriptor_to_reps.into_iter().rev() {
+            let mut reps_digits = Vec::with_capacity(reps.len());
+            for rep in reps {
+                let diff = descriptor - rep;
+                let (described, best_digit) = match slots_to_vars.entry(diff) {
+                    btree_map::Entry::Occupied(mut digit_set) => {
+                        let best_digit =
+                            digit_set.get_mut().pop_last().unwrap();
+                        if !res.vars.contains_key(&diff) {
+                            (Described::Digit(best_digit), best_digit)
+                        } else {
+                            (Described::Var(diff), best_digit)
+                        }
+                    }
+                    btree_map::Entry::Vacant(_) => {
+                        unreachable!()
+                    }
+                };
+                reps_digits.push((rep, best_digit, described));
+            }
+            reps_digits.sort_by_key(|(rep, best_digit, _)| (*best_digit, *rep));
+            for (rep, _, described) in reps_digits.into_iter().rev() {
+                res.best_solution.push((rep, descriptor, described));
+            }
+        }
+        res
+    }
+
+    /// Number of pairs in each number represented by this solution
+    pub fn n_pairs(&self) -> usize {
+        self.best_solution
+            .iter()
+            .map(|(reps, _, _)| *reps as usize)
+            .sum()
+    }
+
+    /// Number of unique digits in each number represented by this solution
+    pub fn n_unique_digits(&self) -> usize {
+        self.best_solution.len()
+    }
+
+    /// Whether any count is not written as a single digit, or any number
+    /// of repetitions of a pair is written with several digits
+    pub fn has_multi_digit_counts(&self) -> bool {
+        self.best_solution.iter().any(|(reps, desc, _)| {
+            *reps >= self.base || !self.counts.is_single_digit(*desc, self.base)
+        })
+    }
+
+    /// Number of concrete numbers represented by this solution
+    pub fn n_expansions(&self) -> u128 {
+        expand::count_expansions(self)
+    }
+
+    /// The work item that this solution was found in
+    pub fn work_item(&self) -> WorkItem {
+        let mut reps: Vec<u8> = self
+            .best_solution
+            .iter()
+            .map(|(reps, _, _)| *reps)
+            .collect();
+        let mut descriptors: Vec<u8> = self
+            .best_solution
+            .iter()
+            .map(|(_, descriptor, _)| *descriptor)
+            .collect();
+        reps.sort();
+        descriptors.sort();
+        WorkItem {
+            n_unique_digits: self.n_unique_digits(),
+            reps,
+            descriptors,
+        }
+    }
+
+    /// Iterate over every concrete number represented by this solution.
+    /// Digits are distinct across pairs, and pairs whose described digit
+    /// could be any unused digit are expanded as well as var slots.
+    pub fn expand(&self) -> Expansions {
+        Expansions::new(self)
+    }
+
+    /// Render this solution with the row of digits before the row of
+    /// descriptors if `convention` writes digits first
+    pub fn display(&self, convention: Convention) -> SolutionDisplay<'_> {
+        SolutionDisplay {
+            solution: self,
+            convention,
+        }
+    }
+}
+
+impl std::fmt::Display for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display(Convention::default()).fmt(f)
+    }
+}
+
+/// A [`Solution`] rendered with the rows of descriptors and digits in the
+/// order of a [`Convention`]
+pub struct SolutionDisplay<'a> {
+    solution: &'a Solution,
+    convention: Convention,
+}
+
+impl std::fmt::Display for SolutionDisplay<'_> {
+    /// If any count needs several digits, the columns of reps, descriptors
+    /// and digits are separated by spaces and right-aligned
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let solution = self.solution;
+        // Pairs of idents and vars
+        let mut idents_vars: BTreeMap<u8, _> = solution
+            .vars
+            .iter()
+            .map(|(idx, vars)| (*idx, (BTreeSet::<char>::new(), vars)))
+            .collect();
+        let mut new_ident = {
+            let mut count = 0;
+            move || {
+                let ident = solution.counts.var_ident(count);
+                count += 1;
+                ident
+            }
+        };
+        let mut columns = Vec::with_capacity(solution.best_solution.len());
+        for (rep, desc, digit) in &solution.best_solution {
+            let digit = match digit {
+                Described::Digit(d) => solution.counts.digit_char(*d),
+                Described::Var(v) => {
+                    let ident = new_ident();
+                    idents_vars.get_mut(v).unwrap().0.insert(ident);
+                    ident
+                }
+            };
+            columns.push([
+                count_string(*rep, solution.base),
+                solution.counts.render(*desc, solution.base),
+                digit.to_string(),
+            ]);
+        }
+        let separator = if solution.has_multi_digit_counts() {
+            " "
+        } else {
+            ""
+        };
+        let rows = match self.convention {
+            Convention::CountDigit => [
+                (0, "reps:        "),
+                (1, "\ndescriptors: "),
+                (2, "\ndigits:      "),
+            ],
+            Convention::DigitCount => [
+                (0, "reps:        "),
+                (2, "\ndigits:      "),
+                (1, "\ndescriptors: "),
+            ],
+        };
+        for (row, header) in rows {
+            header.fmt(f)?;
+            for (idx, column) in columns.iter().enumerate() {
+                if idx != 0 {
+                    separator.fmt(f)?;
+                }
+                let width = column.iter().map(String::len).max().unwrap();
+                write!(f, "{:>width$}", column[row])?;
+            }
+        }
+        f.write_char('\n')?;
+        if !idents_vars.is_empty() {
+            "where\n".fmt(f)?;
+            for (idents, vars) in idents_vars.into_values().rev() {
+                "  {".fmt(f)?;
+                let idents_len = idents.len();
+                for (idx, ident) in idents.into_iter().enumerate() {
+                    ident.fmt(f)?;
+                    if idx < idents_len - 1 {
+                        ", ".fmt(f)?
+                    }
+                }
+                "} ⊆ {".fmt(f)?;
+                let vars_len = vars.len();
+                for (idx, var) in vars.iter().enumerate() {
+                    solution.counts.digit_char(*var).fmt(f)?;
+                    if idx < vars_len - 1 {
+                        ", ".fmt(f)?
+                    }
+                }
+                "}\n".fmt(f)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that for each unique descriptor, a rep exists such that the
+/// descriptor count is correct
+pub fn check_reps_descriptor_counts(
+    rep_descriptors: &[(u8, u8)],
+    base: u8,
+    counts: Counts,
+) -> Option<Solution> {
+    // Number of times each digit occurs in descriptors
+    let mut unique_descriptor_counts = BTreeMap::new();
+    // possible slots for a digit that occurs k times in descriptors
+    let mut slot_counts = HashMap::with_capacity(rep_descriptors.len());
+    for (rep, descriptor) in rep_descriptors {
+        for digit in counts.digits(*descriptor, base) {
+            unique_descriptor_counts
+                .entry(digit)
+                .and_modify(|count: &mut usize| *count += *rep as usize)
+                .or_insert(*rep as usize);
+        }
+        slot_counts
+            .entry(*descriptor - *rep)
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+    }
+    let mut slots_needed = HashMap::with_capacity(rep_descriptors.len());
+    for unique_descriptor_count in unique_descriptor_counts.values() {
+        slots_needed
+            .entry(unique_descriptor_count)
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+    }
+    for (k, slots_needed) in slots_needed {
+        if let Ok(k) = u8::try_from(*k)
+            && let Some(slots_available) = slot_counts.get(&k)
+            && *slots_available >= slots_needed
+        {
+            continue;
+        } else {
+            return None;
+        }
+    }
+    Some(Solution::new(rep_descriptors, base, counts))
+}
+
+/// Filters applied to reps and descriptors before pairing them, returning
+/// the first filter that rejects them
+pub fn filter_reps_descriptors(
+    reps: &[u8],
+    descriptors: &[u8],
+    base: u8,
+    counts: Counts,
+) -> Result<(), Rejection> {
+    filter::DEFAULT_PIPELINE
+        .filter_reps_descriptors(reps, descriptors, base, counts)
+        .map_err(|filter_idx| Rejection::ALL[filter_idx])
+}
+
+/// Filters applied to reps and descriptors before pairing them
+pub fn check_reps_descriptors(
+    reps: &[u8],
+    descriptors: &[u8],
+    base: u8,
+    counts: Counts,
+) -> bool {
+    filter_reps_descriptors(reps, descriptors, base, counts).is_ok()
+}
+
+/// Filters applied to each pairing of reps and descriptors, returning the
+/// solution if the pairing passes all of them, or the first filter that
+/// rejects it
+pub fn filter_pairing(
+    rep_descriptors: &[(u8, u8)],
+    base: u8,
+    counts: Counts,
+) -> Result<Solution, Rejection> {
+    filter::DEFAULT_PIPELINE
+        .filter_pairing(rep_descriptors, base, counts)
+        .map_err(|filter_idx| Rejection::ALL[filter_idx])
+}
+
+/// Filters applied to each pairing of reps and descriptors, returning the
+/// solution if the pairing passes all of them
+pub fn check_pairing(
+    rep_descriptors: &[(u8, u8)],
+    base: u8,
+    counts: Counts,
+) -> Option<Solution> {
+    filter_pairing(rep_descriptors, base, counts).ok()
+}
+
+/// A unit of work in the search: a candidate set of reps and descriptors
+/// for a fixed number of unique digits.
+/// Work items are ordered in the order that they are searched, where
+/// descriptors are searched in order of their sum, which is the number of
+/// digits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkItem {
+    pub n_unique_digits: usize,
+    pub reps: Vec<u8>,
+    pub descriptors: Vec<u8>,
+}
+
+impl WorkItem {
+    /// Number of digits in each number represented by this work item
+    pub fn n_digits(&self) -> usize {
+        self.descriptors.iter().copied().map(usize::from).sum()
+    }
+
+    /// All solutions in base `base` for this work item, with counts written
+    /// as `counts`
+    pub fn solve(&self, base: u8, counts: Counts) -> Vec<Solution> {
+        self.solve_pipeline(base, counts, &filter::DEFAULT_PIPELINE)
+    }
+
+    /// All solutions in base `base` for this work item, with counts written
+    /// as `counts`, that pass the filters of `pipeline`
+    pub fn solve_pipeline(
+        &self,
+        base: u8,
+        counts: Counts,
+        pipeline: &Pipeline,
+    ) -> Vec<Solution> {
+        let mut res = Vec::new();
+        if pipeline
+            .filter_reps_descriptors(
+                &self.reps,
+                &self.descriptors,
+                base,
+                counts,
+            )
+            .is_err()
+        {
+            return res;
+        }
+        let mut rep_descriptors =
+            OrderedPairings::new(&self.reps, &self.descriptors);
+        while let Some(rep_descriptors) = rep_descriptors.next() {
+            if let Ok(solution) =
+                pipeline.filter_pairing(rep_descriptors, base, counts)
+            {
+                res.push(solution);
+            }
+        }
+        res
+    }
+}
+
+impl Ord for WorkItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (
+            self.n_unique_digits,
+            &self.reps,
+            self.n_digits(),
+            &self.descriptors,
+        )
+            .cmp(&(
+                other.n_unique_digits,
+                &other.reps,
+                other.n_digits(),
+                &other.descriptors,
+            ))
+    }
+}
+
+impl PartialOrd for WorkItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A deterministic slice of the work items in a search, for splitting a
+/// search across independent processes.
+/// Work items are assigned to shards in turn, so the `index`'th shard of
+/// `n_shards` searches every work item whose index is `index` modulo
+/// `n_shards`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Shard {
+    pub index: usize,
+    pub n_shards: usize,
+}
+
+impl Shard {
+    /// Whether the work item at `work_item_idx` belongs to this shard
+    pub fn contains(&self, work_item_idx: usize) -> bool {
+        work_item_idx % self.n_shards == self.index
+    }
+}
+
+impl Default for Shard {
+    /// A single shard containing every work item
+    fn default() -> Self {
+        Self {
+            index: 0,
+            n_shards: 1,
+        }
+    }
+}
+
+/// Iterate over work items in base `base` with `n_pairs` pairs and exactly
+/// `n_unique_digits` unique digits, with counts written as `counts`
+pub fn unique_digits_work_items(
+    n_pairs: usize,
+    base: u8,
+    n_unique_digits: usize,
+    counts: Counts,
+) -> impl Iterator<Item = WorkItem> {
+    let max_part = counts.max_count(n_pairs, base);
+    PartitionsParts::new(n_unique_digits, n_pairs, max_part).flat_map(
+        move |reps| {
+            counts
+                .n_digits_range(n_pairs, base)
+                .flat_map(move |n_digits| {
+                    let reps = reps.clone();
+                    PartitionsParts::new(n_unique_digits, n_digits, max_part)
+                        .map(move |descriptors| WorkItem {
+                            n_unique_digits,
+                            reps: reps.clone(),
+                            descriptors,
+                        })
+                })
+        },
+    )
+}
+
+/// The numbers of unique digits in `unique_digits` that are possible with
+/// `n_pairs` pairs in base `base`
+pub fn unique_digits_range(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+) -> RangeInclusive<usize> {
+    let start = match unique_digits.start_bound() {
+        Bound::Included(start) => (*start).max(1),
+        Bound::Excluded(start) => start + 1,
+        Bound::Unbounded => 1,
+    };
+    let max_end = n_pairs.min(base as usize);
+    let end = match unique_digits.end_bound() {
+        Bound::Included(end) => (*end).min(max_end),
+        Bound::Excluded(end) => end.saturating_sub(1).min(max_end),
+        Bound::Unbounded => max_end,
+    };
+    start..=end
+}
+
+/// Iterate over work items in base `base` with `n_pairs` pairs and a number
+/// of unique digits in `unique_digits`, with counts written as `counts`,
+/// in order of the number of unique digits
+pub fn work_items(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    counts: Counts,
+) -> impl Iterator<Item = WorkItem> {
+    unique_digits_range(n_pairs, base, unique_digits).flat_map(
+        move |n_unique_digits| {
+            unique_digits_work_items(n_pairs, base, n_unique_digits, counts)
+        },
+    )
+}
+
+/// Number of work items in base `base` with `n_pairs` pairs and a number of
+/// unique digits in `unique_digits`, with counts written as `counts`,
+/// without enumerating them
+pub fn count_work_items(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    counts: Counts,
+) -> u128 {
+    let max_part = counts.max_count(n_pairs, base);
+    unique_digits_range(n_pairs, base, unique_digits)
+        .map(|n_unique_digits| {
+            let n_descriptors: u128 = counts
+                .n_digits_range(n_pairs, base)
+                .map(|n_digits| {
+                    PartitionsParts::count(n_unique_digits, n_digits, max_part)
+                })
+                .sum();
+            PartitionsParts::count(n_unique_digits, n_pairs, max_part)
+                * n_descriptors
+        })
+        .sum()
+}
+
+/// Iterate over the work items in `shard`, in base `base` with `n_pairs`
+/// pairs and a number of unique digits in `unique_digits`, with counts
+/// written as `counts`, in order of the number of unique digits
+pub fn shard_work_items(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    shard: Shard,
+    counts: Counts,
+) -> impl Iterator<Item = WorkItem> {
+    work_items(n_pairs, base, unique_digits, counts)
+        .enumerate()
+        .filter(move |(work_item_idx, _)| shard.contains(*work_item_idx))
+        .map(|(_, work_item)| work_item)
+}
+
+/// Iterate over solutions in base `base` with `n_pairs` pairs and exactly
+/// `n_unique_digits` unique digits
+pub fn solve_unique_digits_iter(
+    n_pairs: usize,
+    base: u8,
+    n_unique_digits: usize,
+) -> SolveIter {
+    SolveIter::new(n_pairs, base, n_unique_digits..=n_unique_digits)
+}
+
+/// Iterate over solutions in base `base` with `n_pairs` pairs and a number
+/// of unique digits in `unique_digits`, in order of the number of unique
+/// digits
+pub fn solve_iter(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+) -> SolveIter {
+    SolveIter::new(n_pairs, base, unique_digits)
+}
+
+/// Iterate over the solutions in `shard`, in base `base` with `n_pairs`
+/// pairs and a number of unique digits in `unique_digits`, with counts
+/// written as `counts`.
+/// Solutions are yielded in the same order as [`solve_iter`], which is also
+/// the order of their [`Solution::work_item`].
+pub fn solve_shard_iter(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    shard: Shard,
+    counts: Counts,
+) -> SolveIter {
+    SolveIter::new_shard(n_pairs, base, unique_digits, shard, counts)
+}
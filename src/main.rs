@@ -1,317 +1,1638 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map},
-    fmt::Write,
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{IsTerminal as _, Write as _},
+    ops::RangeInclusive,
+    path::PathBuf,
+    task::Poll,
+    time::{Duration, Instant},
 };
+#[cfg(feature = "serde")]
+use std::{fs::File, io::BufRead as _, path::Path};
 
-use clap::Parser;
-use lender::Lender;
-
-mod ordered_pairings;
-mod partition_parts;
-
-use ordered_pairings::OrderedPairings;
-use partition_parts::PartitionsParts;
-
-/// If n_unique_descriptors < n_unique_digits, then
-/// (n_unique_digits - n_unique_descriptors) values from reps must appear
-/// in descriptors.
-/// If there are n_ones 1s in the descriptors, then
-/// n_unique_descriptors + n_ones - 1 must be less than or equal to n_unique_digits.
-fn check_free_vars(reps: &[u8], descriptors: &[u8]) -> bool {
-    let n_unique_digits = descriptors.len();
-    let n_unique_descriptors = HashSet::<&u8>::from_iter(descriptors).len();
-    let n_ones = descriptors.iter().take_while(|d| **d == 1).count();
-    if n_ones != 0 && n_unique_descriptors + n_ones - 1 > n_unique_digits {
-        false
-    } else if n_unique_descriptors == n_unique_digits {
-        true
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+#[cfg(feature = "serde")]
+use self_describing_numbers::{Checkpoint, WorkItem};
+use self_describing_numbers::{
+    Convention, Counts, Described, Extreme, PairOrder, PairsCount, Pipeline,
+    Shard, Solution, SolveIter, Stats, UniqueDigitsCount, count_orderings,
+    count_solutions, count_string, count_word, count_work_items, counts_string,
+    describe_trajectory, digit_char, digits_string, extreme_number,
+    letter_char, self_descriptive_cnf, self_descriptive_model,
+    solve_autobiographical, solve_cycles, solve_shard_iter,
+    solve_shard_par_iter, unique_digits_range, verify,
+    verify::{ParseError, parse_digits},
+    verify_autobiographical,
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+enum Format {
+    /// Human-readable solution patterns
+    Text,
+    /// A single JSON array of records
+    #[cfg(feature = "serde")]
+    Json,
+    /// One JSON record per line
+    #[cfg(feature = "serde")]
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Format of an exported constraint system
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Boolean satisfiability in the DIMACS CNF format
+    Dimacs,
+    /// Integer linear program in the CPLEX LP format
+    Lp,
+    /// Integer linear program in the free MPS format
+    Mps,
+}
+
+/// Machine-readable form of a solution
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    n_pairs: usize,
+    n_unique_digits: usize,
+    #[serde(flatten)]
+    solution: &'a Solution,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> Record<'a> {
+    fn new(solution: &'a Solution) -> Self {
+        Self {
+            n_pairs: solution.n_pairs(),
+            n_unique_digits: solution.n_unique_digits(),
+            solution,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Print a JSON array with one record per line
+#[cfg(feature = "serde")]
+fn print_json_array(records: impl Iterator<Item = String>) {
+    println!("[");
+    for (idx, record) in records.enumerate() {
+        if idx != 0 {
+            println!(",");
+        }
+        print!("  {record}");
+    }
+    println!("\n]");
+}
+
+const CSV_HEADER: &str = "n_pairs,n_unique_digits,reps,descriptors,digits,vars";
+
+/// Render the (count, digit) pairs of a number whose counts are words as a
+/// sentence, such as `five v, five i, five f, five e`
+fn sentence_string(pairs: &[(u8, u8)], convention: Convention) -> String {
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(count, letter)| {
+            let (count, letter) = (count_word(*count), letter_char(*letter));
+            match convention {
+                Convention::CountDigit => format!("{count} {letter}"),
+                Convention::DigitCount => format!("{letter} {count}"),
+            }
+        })
+        .collect();
+    pairs.join(", ")
+}
+
+/// Render a solution as a CSV row.
+/// Var slots are rendered as idents in the `digits` column, and the `vars`
+/// column lists each group of idents with its possible digits, such as
+/// `ab:24`.
+/// If any count needs several digits, the reps, descriptors and digits of
+/// each pair are separated by spaces.
+fn csv_record(solution: &Solution) -> String {
+    let separator = if solution.has_multi_digit_counts() {
+        " "
     } else {
-        let mut reps_required_in_descriptors =
-            n_unique_digits - n_unique_descriptors;
-        let mut reps = reps.iter();
-        while reps_required_in_descriptors > 0 {
-            let Some(rep) = reps.next() else { return false };
-            if descriptors.iter().any(|desc| rep == desc) {
-                reps_required_in_descriptors -= 1;
+        ""
+    };
+    let mut reps = Vec::new();
+    let mut descriptors = Vec::new();
+    let mut digits = String::new();
+    let mut idents_vars = BTreeMap::<u8, String>::new();
+    let mut n_idents = 0;
+    for (idx, (rep, desc, digit)) in solution.best_solution.iter().enumerate() {
+        reps.push(count_string(*rep, solution.base));
+        descriptors.push(solution.counts.render(*desc, solution.base));
+        if idx != 0 {
+            digits.push_str(separator);
+        }
+        match digit {
+            Described::Digit(d) => digits.push(solution.counts.digit_char(*d)),
+            Described::Var(v) => {
+                let ident = solution.counts.var_ident(n_idents);
+                n_idents += 1;
+                idents_vars.entry(*v).or_default().push(ident);
+                digits.push(ident);
             }
         }
-        true
     }
+    let vars: Vec<String> = idents_vars
+        .into_iter()
+        .rev()
+        .map(|(v, idents)| {
+            let vars: String = solution.vars[&v]
+                .iter()
+                .map(|var| solution.counts.digit_char(*var))
+                .collect();
+            format!("{idents}:{vars}")
+        })
+        .collect();
+    format!(
+        "{},{},{},{},{digits},{}",
+        solution.n_pairs(),
+        solution.n_unique_digits(),
+        reps.join(separator),
+        descriptors.join(separator),
+        vars.join(" ")
+    )
 }
 
-/// Each descriptor's reps must be less than or equal to the descriptor.
-/// For the descriptor 9, the greatest possible number of reps is 7.
-fn check_reps_lte_descriptor(rep_descriptors: &[(u8, u8)]) -> bool {
-    rep_descriptors.iter().all(
-        |(reps, descriptor)| reps <= descriptor, /* && (*descriptor != 9 || *reps < 7) */
-    )
+/// Renders solutions, or the concrete numbers they represent, into a
+/// buffer that is flushed to stdout.
+/// The state of the printer is saved in checkpoints, so that a resumed
+/// search continues the output where it stopped.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct Printer {
+    base: u8,
+    unique_digits: RangeInclusive<usize>,
+    format: Format,
+    expand: bool,
+    /// Order of the pairs of expanded numbers
+    #[cfg_attr(feature = "serde", serde(default))]
+    order: PairOrder,
+    /// Print the number of distinct orderings of the pairs of each
+    /// expanded number
+    #[cfg_attr(feature = "serde", serde(default))]
+    count_orderings: bool,
+    /// Whether pairs are written count first or digit first
+    #[cfg_attr(feature = "serde", serde(default))]
+    convention: Convention,
+    /// Print a header for each pair count
+    grouped: bool,
+    /// Number of records printed, in JSON format
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    n_records: usize,
+    /// Number of unique digits of the last header printed, in text format
+    n_unique_digits: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    buf: String,
 }
 
-/// If a rep is equal to a descriptor, then we need a free var
-fn check_free_vars_rep_descriptor(rep_descriptors: &[(u8, u8)]) -> bool {
-    let n_unique_digits = rep_descriptors.len();
-    let mut n_unique_descriptors = 0usize;
-    // i'th element indicates if the descriptor i+1 exists in descriptors
-    let mut descriptor_used = [false; 9];
-    let mut free_vars_needed = 0usize;
-    for (rep, d) in rep_descriptors {
-        if !std::mem::replace(&mut descriptor_used[(d - 1) as usize], true) {
-            n_unique_descriptors += 1;
-        }
-        if rep == d {
-            free_vars_needed += 1;
-        }
-    }
-    let free_vars = n_unique_digits - n_unique_descriptors;
-    free_vars == free_vars_needed
-}
-
-enum Described {
-    Digit(u8),
-    /// Index of the var set
-    Var(u8),
-}
-
-struct Solution {
-    best_solution: Vec<(u8, u8, Described)>,
-    vars: BTreeMap<u8, BTreeSet<u8>>,
-}
-
-impl Solution {
-    fn new(rep_descriptors: &[(u8, u8)]) -> Self {
-        let mut unique_descriptor_counts = BTreeMap::new();
-        // possible slots for a digit that occurs k times in descriptors
-        let mut slots_to_rep_descriptors =
-            HashMap::<u8, Vec<_>>::with_capacity(rep_descriptors.len());
-        let mut descriptor_to_reps = BTreeMap::<u8, Vec<u8>>::new();
-        for (reps, descriptor) in rep_descriptors {
-            unique_descriptor_counts
-                .entry(*descriptor)
-                .and_modify(|count| *count += *reps)
-                .or_insert(*reps);
-            slots_to_rep_descriptors
-                .entry(*descriptor - *reps)
-                .or_default()
-                .push((reps, descriptor));
-            descriptor_to_reps
-                .entry(*descriptor)
-                .or_default()
-                .push(*reps);
-        }
-        for rep_descriptors in slots_to_rep_descriptors.values_mut() {
-            rep_descriptors
-                .sort_by_key(|(reps, descriptor)| (*descriptor, *reps));
-        }
-        for reps in descriptor_to_reps.values_mut() {
-            reps.sort();
-        }
-        let n_unique_descriptors = unique_descriptor_counts.len();
-        let n_free_vars = rep_descriptors.len() - n_unique_descriptors;
-        let mut slots_to_vars = BTreeMap::<u8, BTreeSet<u8>>::new();
-        if n_free_vars != 0 {
-            let possible_free_vars: BTreeSet<u8> = (0..=9)
-                .filter(|d| !unique_descriptor_counts.contains_key(d))
-                .collect();
-            slots_to_vars.insert(0, possible_free_vars);
+impl Printer {
+    fn new(
+        base: u8,
+        unique_digits: RangeInclusive<usize>,
+        format: Format,
+        expand: Option<PairOrder>,
+        count_orderings: bool,
+        convention: Convention,
+        grouped: bool,
+    ) -> Self {
+        Self {
+            base,
+            unique_digits,
+            format,
+            expand: expand.is_some(),
+            order: expand.unwrap_or_default(),
+            count_orderings,
+            convention,
+            grouped,
+            n_records: 0,
+            n_unique_digits: 0,
+            buf: String::new(),
         }
-        for (descriptor, count) in unique_descriptor_counts {
-            slots_to_vars.entry(count).or_default().insert(descriptor);
+    }
+
+    fn flush(&mut self) {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(self.buf.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        self.buf.clear();
+    }
+
+    fn start(&mut self) {
+        if self.expand {
+            return;
         }
-        let mut res = Self {
-            best_solution: Vec::with_capacity(rep_descriptors.len()),
-            vars: {
-                let slots_to_unique_rep_descriptor_counts: HashMap<u8, usize> =
-                    slots_to_rep_descriptors
-                        .iter()
-                        .map(|(slot, rds)| {
-                            let mut rds = rds.clone();
-                            rds.dedup();
-                            (*slot, rds.len())
-                        })
-                        .collect();
-                let mut vars = slots_to_vars.clone();
-                vars.retain(|slot, _vs| {
-                    slots_to_unique_rep_descriptor_counts[slot] > 1
-                });
-                vars
-            },
-        };
-        for (descriptor, reps) in descriptor_to_reps.into_iter().rev() {
-            let mut reps_digits = Vec::with_capacity(reps.len());
-            for rep in reps {
-                let diff = descriptor - rep;
-                let (described, best_digit) = match slots_to_vars.entry(diff) {
-                    btree_map::Entry::Occupied(mut digit_set) => {
-                        let best_digit =
-                            digit_set.get_mut().pop_last().unwrap();
-                        if !res.vars.contains_key(&diff) {
-                            (Described::Digit(best_digit), best_digit)
-                        } else {
-                            (Described::Var(diff), best_digit)
-                        }
-                    }
-                    btree_map::Entry::Vacant(_) => {
-                        unreachable!()
-                    }
+        match self.format {
+            Format::Text => (),
+            #[cfg(feature = "serde")]
+            Format::Ndjson => (),
+            #[cfg(feature = "serde")]
+            Format::Json => writeln!(self.buf, "[").unwrap(),
+            Format::Csv => writeln!(self.buf, "{CSV_HEADER}").unwrap(),
+        }
+    }
+
+    fn start_pairs(&mut self, n_pairs: usize) {
+        if self.expand || matches!(self.format, Format::Text) {
+            if self.grouped {
+                writeln!(self.buf, "{n_pairs} PAIRS:").unwrap();
+            }
+            let unique_digits = unique_digits_range(
+                n_pairs,
+                self.base,
+                self.unique_digits.clone(),
+            );
+            self.n_unique_digits = unique_digits.start() - 1;
+        }
+    }
+
+    /// Print headers for each number of unique digits up to and including
+    /// `n_unique_digits`
+    fn unique_digits_headers(&mut self, n_unique_digits: usize) {
+        while self.n_unique_digits < n_unique_digits {
+            self.n_unique_digits += 1;
+            writeln!(self.buf, "{} UNIQUE DIGITS:", self.n_unique_digits)
+                .unwrap();
+        }
+    }
+
+    fn solution(&mut self, solution: &Solution) {
+        if self.expand {
+            let orderings = if self.count_orderings {
+                match count_orderings(solution) {
+                    Some(n_orderings) => format!(" ({n_orderings} orderings)"),
+                    None => format!(" (more than {} orderings)", u128::MAX),
+                }
+            } else {
+                String::new()
+            };
+            for mut pairs in solution.expand().pairs() {
+                let number = if solution.counts == Counts::Words {
+                    self.order.sort(&mut pairs);
+                    sentence_string(&pairs, self.convention)
+                } else {
+                    let digits = self.order.apply(
+                        &pairs,
+                        self.base,
+                        solution.counts,
+                        self.convention,
+                    );
+                    digits_string(&digits)
                 };
-                reps_digits.push((rep, best_digit, described));
+                writeln!(self.buf, "{number}{orderings}").unwrap();
+            }
+            return;
+        }
+        match self.format {
+            Format::Text => {
+                self.unique_digits_headers(solution.n_unique_digits());
+                writeln!(self.buf, "{}", solution.display(self.convention))
+                    .unwrap();
+            }
+            #[cfg(feature = "serde")]
+            Format::Json => {
+                if self.n_records != 0 {
+                    writeln!(self.buf, ",").unwrap();
+                }
+                write!(self.buf, "  {}", Record::new(solution).to_json())
+                    .unwrap();
+                self.n_records += 1;
+            }
+            #[cfg(feature = "serde")]
+            Format::Ndjson => {
+                writeln!(self.buf, "{}", Record::new(solution).to_json())
+                    .unwrap();
             }
-            reps_digits.sort_by_key(|(rep, best_digit, _)| (*best_digit, *rep));
-            for (rep, _, described) in reps_digits.into_iter().rev() {
-                res.best_solution.push((rep, descriptor, described));
+            Format::Csv => {
+                writeln!(self.buf, "{}", csv_record(solution)).unwrap()
             }
         }
-        res
+    }
+
+    fn end_pairs(&mut self, n_pairs: usize) {
+        if !self.expand && matches!(self.format, Format::Text) {
+            let unique_digits = unique_digits_range(
+                n_pairs,
+                self.base,
+                self.unique_digits.clone(),
+            );
+            self.unique_digits_headers(*unique_digits.end());
+        }
+    }
+
+    fn end(&mut self) {
+        #[cfg(feature = "serde")]
+        if !self.expand && matches!(self.format, Format::Json) {
+            writeln!(self.buf, "\n]").unwrap();
+        }
     }
 }
 
-impl std::fmt::Display for Solution {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        "reps:        ".fmt(f)?;
-        for (rep, _desc, _digit) in &self.best_solution {
-            rep.fmt(f)?;
-        }
-        "\ndescriptors: ".fmt(f)?;
-        for (_rep, desc, _digit) in &self.best_solution {
-            desc.fmt(f)?;
-        }
-        "\ndigits:      ".fmt(f)?;
-        // Pairs of idents and vars
-        let mut idents_vars: BTreeMap<u8, _> = self
-            .vars
-            .iter()
-            .map(|(idx, vars)| (*idx, (BTreeSet::<char>::new(), vars)))
-            .collect();
-        let mut new_ident = {
-            const LOWERCASE_A_ASCII: u8 = 0x61;
-            let mut count = 0;
-            move || {
-                let ident = if count < 8 {
-                    char::from(LOWERCASE_A_ASCII + count)
-                } else {
-                    // Avoid using `i` as an ident
-                    char::from(LOWERCASE_A_ASCII + count + 1)
-                };
-                count += 1;
-                ident
+/// Print solutions for each pair count
+fn print_solutions(
+    pair_counts: &[usize],
+    printer: &mut Printer,
+    solutions: impl Fn(usize) -> Solutions,
+) {
+    printer.start();
+    for n_pairs in pair_counts {
+        printer.start_pairs(*n_pairs);
+        for solution in solutions(*n_pairs) {
+            printer.solution(&solution);
+            printer.flush();
+        }
+        printer.end_pairs(*n_pairs);
+    }
+    printer.end();
+    printer.flush();
+}
+
+/// Number of pairings to examine between progress reports and checkpoints
+const POLL_PAIRINGS: u64 = 1 << 16;
+
+/// Minimum time between progress reports
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Render a duration as hours, minutes and seconds
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Reports the progress of a search on stderr.
+/// On a terminal, each report replaces the previous one.
+struct ProgressReporter {
+    n_pairs: usize,
+    /// Total number of work items in the search
+    n_work_items: u128,
+    start: Instant,
+    /// Work item index when reporting started, so that the time remaining
+    /// for a resumed search is estimated from its own rate of progress
+    start_work_item_idx: u128,
+    last_report: Instant,
+    is_terminal: bool,
+    reported: bool,
+}
+
+impl ProgressReporter {
+    fn new(
+        n_pairs: usize,
+        base: u8,
+        unique_digits: RangeInclusive<usize>,
+        counts: Counts,
+        solutions: &SolveIter,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            n_pairs,
+            n_work_items: count_work_items(
+                n_pairs,
+                base,
+                unique_digits,
+                counts,
+            ),
+            start: now,
+            start_work_item_idx: solutions
+                .progress()
+                .map_or(0, |progress| progress.work_item_idx),
+            last_report: now,
+            is_terminal: std::io::stderr().is_terminal(),
+            reported: false,
+        }
+    }
+
+    /// Report progress if enough time has passed since the last report
+    fn update(&mut self, solutions: &SolveIter) {
+        if self.last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        let Some(progress) = solutions.progress() else {
+            return;
+        };
+        self.last_report = Instant::now();
+        let elapsed = self.start.elapsed();
+        let n_done = progress.work_item_idx - self.start_work_item_idx;
+        let n_remaining = self.n_work_items - progress.work_item_idx;
+        let eta = if n_done == 0 {
+            "unknown".to_owned()
+        } else {
+            let secs_remaining =
+                elapsed.as_secs_f64() * n_remaining as f64 / n_done as f64;
+            format_duration(Duration::from_secs_f64(secs_remaining))
+        };
+        let line = format!(
+            "{} pairs: {} unique digits, reps {}/{}, descriptors {}/{}, \
+             {:.2}% of work items, {:.0} pairings/s, {} remaining",
+            self.n_pairs,
+            progress.n_unique_digits,
+            progress.reps_idx + 1,
+            progress.n_reps,
+            progress.descriptors_idx + 1,
+            progress.n_descriptors,
+            progress.work_item_idx as f64 * 100.0 / self.n_work_items as f64,
+            progress.n_pairings as f64 / elapsed.as_secs_f64(),
+            eta,
+        );
+        if self.is_terminal {
+            // Clear the rest of the previous report
+            eprint!("\r{line}\x1b[K");
+        } else {
+            eprintln!("{line}");
+        }
+        self.reported = true;
+    }
+
+    /// End the last report on a terminal
+    fn finish(&mut self) {
+        if self.is_terminal && self.reported {
+            eprintln!();
+        }
+        self.reported = false;
+    }
+}
+
+/// Diagnostics to report on stderr during a serial search
+#[derive(Clone, Copy)]
+struct Reporting {
+    progress: bool,
+    stats: bool,
+    trace: bool,
+}
+
+/// Render the reps and descriptors of a pairing in base `base`
+fn pairing_string(rep_descriptors: &[(u8, u8)], base: u8) -> String {
+    let reps =
+        counts_string(rep_descriptors.iter().map(|(reps, _)| *reps), base);
+    let descriptors = counts_string(
+        rep_descriptors.iter().map(|(_, descriptor)| *descriptor),
+        base,
+    );
+    format!("reps {reps}, descriptors {descriptors}")
+}
+
+/// Print the number of candidates rejected by each filter
+fn print_stats(n_pairs: usize, stats: &Stats, pipeline: &Pipeline) {
+    eprintln!(
+        "{n_pairs} pairs: {} work items, {} pairings, {} solutions",
+        stats.n_work_items, stats.n_pairings, stats.n_solutions
+    );
+    eprintln!(
+        "{:<32}{:>16}{:>16}{:>8}",
+        "filter", "candidates", "rejected", "%"
+    );
+    for (filter, n_candidates, n_rejected) in stats.funnel(pipeline) {
+        let percent = if n_candidates == 0 {
+            0.0
+        } else {
+            n_rejected as f64 * 100.0 / n_candidates as f64
+        };
+        eprintln!(
+            "{:<32}{n_candidates:>16}{n_rejected:>16}{percent:>8.2}",
+            filter.name()
+        );
+    }
+}
+
+/// Solutions from a serial search, reporting diagnostics while searching
+struct Search {
+    n_pairs: usize,
+    solutions: SolveIter,
+    reporter: Option<ProgressReporter>,
+    stats: bool,
+    finished: bool,
+}
+
+impl Search {
+    fn new(
+        n_pairs: usize,
+        base: u8,
+        unique_digits: RangeInclusive<usize>,
+        counts: Counts,
+        mut solutions: SolveIter,
+        reporting: Reporting,
+    ) -> Self {
+        if reporting.trace {
+            solutions = solutions.with_trace(move |rep_descriptors, filter| {
+                eprintln!(
+                    "{n_pairs} pairs: {} rejected by {}",
+                    pairing_string(rep_descriptors, base),
+                    filter.name()
+                )
+            });
+        }
+        let reporter = reporting.progress.then(|| {
+            ProgressReporter::new(
+                n_pairs,
+                base,
+                unique_digits,
+                counts,
+                &solutions,
+            )
+        });
+        Self {
+            n_pairs,
+            solutions,
+            reporter,
+            stats: reporting.stats,
+            finished: false,
+        }
+    }
+
+    /// Search for a bounded number of pairings, like
+    /// [`SolveIter::poll_next`]
+    fn poll_next(&mut self) -> Poll<Option<Solution>> {
+        let solution = self.solutions.poll_next(POLL_PAIRINGS);
+        if let Some(reporter) = &mut self.reporter {
+            reporter.update(&self.solutions);
+        }
+        if let Poll::Ready(None) = solution
+            && !std::mem::replace(&mut self.finished, true)
+        {
+            if let Some(reporter) = &mut self.reporter {
+                reporter.finish();
+            }
+            if self.stats {
+                print_stats(
+                    self.n_pairs,
+                    self.solutions.stats(),
+                    self.solutions.pipeline(),
+                );
+            }
+        }
+        solution
+    }
+}
+
+impl Iterator for Search {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Poll::Ready(solution) = self.poll_next() {
+                return solution;
+            }
+        }
+    }
+}
+
+/// Solutions read from the output of a shard
+#[cfg(feature = "serde")]
+struct ShardOutput {
+    path: PathBuf,
+    lines: std::iter::Enumerate<std::io::Lines<std::io::BufReader<File>>>,
+    /// The next solution, and its work item
+    next: Option<(WorkItem, Solution)>,
+}
+
+#[cfg(feature = "serde")]
+impl ShardOutput {
+    fn open(path: PathBuf) -> Self {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("Failed to open `{}`: {err}", path.display());
+                std::process::exit(1)
             }
         };
-        for (_rep, _desc, digit) in &self.best_solution {
-            match digit {
-                Described::Digit(d) => d.fmt(f)?,
-                Described::Var(v) => {
-                    let ident = new_ident();
-                    idents_vars.get_mut(v).unwrap().0.insert(ident);
-                    ident.fmt(f)?;
+        let lines = std::io::BufReader::new(file).lines().enumerate();
+        let mut res = Self {
+            path,
+            lines,
+            next: None,
+        };
+        res.advance();
+        res
+    }
+
+    /// Read the next solution, checking that solutions are in search order
+    fn advance(&mut self) {
+        let prev = self.next.take();
+        for (line_idx, line) in self.lines.by_ref() {
+            let solution =
+                line.map_err(|err| err.to_string()).and_then(|line| {
+                    if line.trim().is_empty() {
+                        return Ok(None);
+                    }
+                    serde_json::from_str::<Solution>(&line)
+                        .map(Some)
+                        .map_err(|err| err.to_string())
+                });
+            let solution = match solution {
+                Ok(Some(solution)) => solution,
+                Ok(None) => continue,
+                Err(err) => {
+                    eprintln!(
+                        "Failed to read line {} of `{}`: {err}",
+                        line_idx + 1,
+                        self.path.display()
+                    );
+                    std::process::exit(1)
                 }
+            };
+            let work_item = solution.work_item();
+            if let Some((prev_work_item, prev_solution)) = &prev
+                && prev_solution.n_pairs() == solution.n_pairs()
+                && *prev_work_item > work_item
+            {
+                eprintln!(
+                    "Line {} of `{}` is not in search order",
+                    line_idx + 1,
+                    self.path.display()
+                );
+                std::process::exit(1)
             }
+            self.next = Some((work_item, solution));
+            return;
         }
-        f.write_char('\n')?;
-        if !idents_vars.is_empty() {
-            "where\n".fmt(f)?;
-            for (idents, vars) in idents_vars.into_values().rev() {
-                "  {".fmt(f)?;
-                let idents_len = idents.len();
-                for (idx, ident) in idents.into_iter().enumerate() {
-                    ident.fmt(f)?;
-                    if idx < idents_len - 1 {
-                        ", ".fmt(f)?
-                    }
+    }
+}
+
+/// Print the solutions from the outputs of each shard, in the same order as
+/// a single search
+#[cfg(feature = "serde")]
+fn print_merged(
+    pair_counts: &[usize],
+    printer: &mut Printer,
+    mut shards: Vec<ShardOutput>,
+) {
+    printer.start();
+    for n_pairs in pair_counts.iter().copied() {
+        printer.start_pairs(n_pairs);
+        // Each work item is searched by a single shard, so the next
+        // solution is the first in search order among the shards
+        while let Some(shard) = shards
+            .iter_mut()
+            .filter(|shard| {
+                shard
+                    .next
+                    .as_ref()
+                    .is_some_and(|(_, solution)| solution.n_pairs() == n_pairs)
+            })
+            .min_by_key(|shard| {
+                shard.next.as_ref().map(|(work_item, _)| work_item.clone())
+            })
+        {
+            let (_, solution) = shard.next.as_ref().unwrap();
+            printer.solution(solution);
+            printer.flush();
+            shard.advance();
+        }
+        printer.end_pairs(n_pairs);
+    }
+    printer.end();
+    printer.flush();
+    for shard in shards {
+        if let Some((_, solution)) = shard.next {
+            eprintln!(
+                "`{}` contains solutions with {} pairs that are not in \
+                 PAIRS, or not in the order of PAIRS",
+                shard.path.display(),
+                solution.n_pairs()
+            );
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Progress of a search over several pair counts, saved to a checkpoint
+/// file
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Run {
+    pair_counts: Vec<usize>,
+    shard: Shard,
+    #[serde(default)]
+    counts: Counts,
+    /// Index of the current pair count
+    pairs_idx: usize,
+    printer: Printer,
+    /// Search for the current pair count, or `None` if it has not started
+    search: Option<Checkpoint>,
+}
+
+#[cfg(feature = "serde")]
+impl Run {
+    fn load(path: &Path) -> Self {
+        let res = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_str(&json).map_err(|err| err.to_string())
+            });
+        match res {
+            Ok(run) => run,
+            Err(err) => {
+                eprintln!(
+                    "Failed to read checkpoint `{}`: {err}",
+                    path.display()
+                );
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// Write the checkpoint to a temporary file, and then move it into
+    /// place, so that a checkpoint file is never partially written
+    fn save(&self, path: &Path) {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let json = serde_json::to_string(self).unwrap();
+        if let Err(err) = std::fs::write(&tmp_path, json)
+            .and_then(|()| std::fs::rename(&tmp_path, path))
+        {
+            eprintln!("Failed to write checkpoint `{}`: {err}", path.display());
+            std::process::exit(1)
+        }
+    }
+
+    /// Continue the search, saving a checkpoint to `path` every `interval`.
+    /// Output is only flushed when a checkpoint is saved, so that resuming
+    /// from the last checkpoint does not repeat any output.
+    fn print_solutions(
+        mut self,
+        path: &Path,
+        interval: Duration,
+        reporting: Reporting,
+    ) {
+        if self.pairs_idx == self.pair_counts.len() {
+            eprintln!("The search in the checkpoint is already finished");
+            return;
+        }
+        let mut last_save = Instant::now();
+        if self.pairs_idx == 0 && self.search.is_none() {
+            self.printer.start();
+        }
+        while let Some(n_pairs) = self.pair_counts.get(self.pairs_idx).copied()
+        {
+            let solutions = match self.search.take() {
+                Some(checkpoint) => SolveIter::resume(checkpoint),
+                None => {
+                    self.printer.start_pairs(n_pairs);
+                    solve_shard_iter(
+                        n_pairs,
+                        self.printer.base,
+                        self.printer.unique_digits.clone(),
+                        self.shard,
+                        self.counts,
+                    )
                 }
-                "} ⊆ {".fmt(f)?;
-                let vars_len = vars.len();
-                for (idx, var) in vars.iter().enumerate() {
-                    var.fmt(f)?;
-                    if idx < vars_len - 1 {
-                        ", ".fmt(f)?
+            };
+            let mut search = Search::new(
+                n_pairs,
+                self.printer.base,
+                self.printer.unique_digits.clone(),
+                self.counts,
+                solutions,
+                reporting,
+            );
+            loop {
+                match search.poll_next() {
+                    Poll::Ready(Some(solution)) => {
+                        self.printer.solution(&solution)
                     }
+                    Poll::Ready(None) => break,
+                    Poll::Pending => (),
                 }
-                "}\n".fmt(f)?
-            }
-        }
-        Ok(())
-    }
-}
-
-/// Check that for each unique descriptor, a rep exists such that the
-/// descriptor count is correct
-fn check_reps_descriptor_counts(
-    rep_descriptors: &[(u8, u8)],
-) -> Option<Solution> {
-    let mut unique_descriptor_counts = BTreeMap::new();
-    // possible slots for a digit that occurs k times in descriptors
-    let mut slot_counts = HashMap::with_capacity(rep_descriptors.len());
-    for (rep, descriptor) in rep_descriptors {
-        unique_descriptor_counts
-            .entry(*descriptor)
-            .and_modify(|count| *count += *rep)
-            .or_insert(*rep);
-        slot_counts
-            .entry(*descriptor - *rep)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-    }
-    let mut slots_needed = HashMap::with_capacity(rep_descriptors.len());
-    for unique_descriptor_count in unique_descriptor_counts.values() {
-        slots_needed
-            .entry(unique_descriptor_count)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-    }
-    for (k, slots_needed) in slots_needed {
-        if let Some(slots_available) = slot_counts.get(k)
-            && *slots_available >= slots_needed
-        {
-            continue;
-        } else {
-            return None;
+                if last_save.elapsed() >= interval {
+                    self.printer.flush();
+                    self.search = Some(search.solutions.checkpoint());
+                    self.save(path);
+                    last_save = Instant::now();
+                }
+            }
+            self.printer.end_pairs(n_pairs);
+            self.pairs_idx += 1;
+            self.search = None;
         }
+        self.printer.end();
+        self.printer.flush();
+        self.save(path);
     }
-    Some(Solution::new(rep_descriptors))
 }
 
-fn solve(n_pairs: usize) {
-    for n_unique_digits in 1..=n_pairs.min(10) {
-        println!("{n_unique_digits} UNIQUE DIGITS:");
-        for reps in PartitionsParts::new(n_unique_digits, n_pairs) {
-            'descrs: for descriptors in
-                PartitionsParts::new(n_unique_digits, n_pairs * 2)
-            {
-                if reps > descriptors {
-                    continue 'descrs;
+const COUNTS_CSV_HEADER: &str = "n_pairs,n_unique_digits,n_solutions,n_numbers";
+
+/// Print counts for each pair count.
+/// In text format, each pair count has its own header if `grouped` is set.
+fn print_counts(
+    counts: impl Iterator<Item = Vec<UniqueDigitsCount>>,
+    format: Format,
+    grouped: bool,
+) {
+    match format {
+        Format::Text => {
+            for counts in counts {
+                let total = PairsCount::new(&counts);
+                if grouped {
+                    println!("{} PAIRS:", total.n_pairs);
                 }
-                if !check_free_vars(&reps, &descriptors) {
-                    continue 'descrs;
+                for count in counts {
+                    println!(
+                        "{} UNIQUE DIGITS: {} solutions, {} numbers",
+                        count.n_unique_digits,
+                        count.n_solutions,
+                        count.n_numbers
+                    );
                 }
-                let mut rep_descriptors =
-                    OrderedPairings::new(&reps, &descriptors);
-                'rep_descriptors: while let Some(rep_descriptors) =
-                    rep_descriptors.next()
-                {
-                    if !check_reps_lte_descriptor(rep_descriptors) {
-                        continue 'rep_descriptors;
-                    }
-                    if !check_free_vars_rep_descriptor(rep_descriptors) {
-                        continue 'rep_descriptors;
-                    }
-                    let Some(solution) =
-                        check_reps_descriptor_counts(rep_descriptors)
-                    else {
-                        continue 'rep_descriptors;
-                    };
-                    println!("{solution}");
+                println!(
+                    "TOTAL: {} solutions, {} numbers",
+                    total.n_solutions, total.n_numbers
+                );
+            }
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => print_json_array(
+            counts
+                .flatten()
+                .map(|count| serde_json::to_string(&count).unwrap()),
+        ),
+        #[cfg(feature = "serde")]
+        Format::Ndjson => {
+            for count in counts.flatten() {
+                println!("{}", serde_json::to_string(&count).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("{COUNTS_CSV_HEADER}");
+            for count in counts.flatten() {
+                println!(
+                    "{},{},{},{}",
+                    count.n_pairs,
+                    count.n_unique_digits,
+                    count.n_solutions,
+                    count.n_numbers
+                );
+            }
+        }
+    }
+}
+
+const SUMMARY_CSV_HEADER: &str = "n_pairs,n_solutions,n_numbers";
+
+/// Print one summary row for each pair count
+fn print_summaries(
+    summaries: impl Iterator<Item = PairsCount>,
+    format: Format,
+) {
+    match format {
+        Format::Text => {
+            for summary in summaries {
+                println!(
+                    "{} PAIRS: {} solutions, {} numbers",
+                    summary.n_pairs, summary.n_solutions, summary.n_numbers
+                );
+            }
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => print_json_array(
+            summaries.map(|summary| serde_json::to_string(&summary).unwrap()),
+        ),
+        #[cfg(feature = "serde")]
+        Format::Ndjson => {
+            for summary in summaries {
+                println!("{}", serde_json::to_string(&summary).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("{SUMMARY_CSV_HEADER}");
+            for summary in summaries {
+                println!(
+                    "{},{},{}",
+                    summary.n_pairs, summary.n_solutions, summary.n_numbers
+                );
+            }
+        }
+    }
+}
+
+/// The smallest or largest concrete number with a given number of pairs
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct ExtremeRecord {
+    n_pairs: usize,
+    /// `None` if there are no solutions
+    number: Option<String>,
+}
+
+const EXTREME_CSV_HEADER: &str = "n_pairs,number";
+
+/// Print the smallest or largest concrete number for each pair count
+fn print_extremes(
+    records: impl Iterator<Item = ExtremeRecord>,
+    format: Format,
+) {
+    match format {
+        Format::Text => {
+            for record in records {
+                println!(
+                    "{} PAIRS: {}",
+                    record.n_pairs,
+                    record.number.as_deref().unwrap_or("none")
+                );
+            }
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => print_json_array(
+            records.map(|record| serde_json::to_string(&record).unwrap()),
+        ),
+        #[cfg(feature = "serde")]
+        Format::Ndjson => {
+            for record in records {
+                println!("{}", serde_json::to_string(&record).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("{EXTREME_CSV_HEADER}");
+            for record in records {
+                println!(
+                    "{},{}",
+                    record.n_pairs,
+                    record.number.unwrap_or_default()
+                );
+            }
+        }
+    }
+}
+
+/// Check a number, printing every wrong pair.
+/// Returns `false` if the number is not self-descriptive.
+fn print_verification(number: &str, base: u8, convention: Convention) -> bool {
+    let verification = match verify(number, base, convention) {
+        Ok(verification) => verification,
+        Err(err) => {
+            eprintln!("Failed to parse `{number}`: {err}");
+            return false;
+        }
+    };
+    for wrong_pair in &verification.wrong_pairs {
+        println!("{wrong_pair}");
+    }
+    for digit in &verification.undescribed {
+        println!("{} occurs without a pair", digit_char(*digit));
+    }
+    let is_self_descriptive = verification.is_self_descriptive();
+    if is_self_descriptive {
+        println!("{number} is self-descriptive");
+    } else {
+        println!("{number} is not self-descriptive");
+    }
+    is_self_descriptive
+}
+
+/// Check an autobiographical number, printing every wrong position.
+/// Returns `false` if the number is not autobiographical.
+fn print_autobiographical_verification(number: &str, base: u8) -> bool {
+    let digits = match parse_digits(number, base) {
+        Ok(digits) if !digits.is_empty() => digits,
+        Ok(_) => {
+            eprintln!("Failed to parse `{number}`: {}", ParseError::Empty);
+            return false;
+        }
+        Err(err) => {
+            eprintln!("Failed to parse `{number}`: {err}");
+            return false;
+        }
+    };
+    let wrong_positions = verify_autobiographical(&digits);
+    for wrong_position in &wrong_positions {
+        println!("{wrong_position}");
+    }
+    let is_autobiographical = wrong_positions.is_empty();
+    if is_autobiographical {
+        println!("{number} is autobiographical");
+    } else {
+        println!("{number} is not autobiographical");
+    }
+    is_autobiographical
+}
+
+/// Iterate the describe map from a number, printing each step and the
+/// length of the trajectory and cycle.
+/// Returns `false` if the number could not be parsed.
+fn print_trajectory(number: &str, base: u8) -> bool {
+    let digits = match parse_digits(number, base) {
+        Ok(digits) if !digits.is_empty() => digits,
+        Ok(_) => {
+            eprintln!("Failed to parse `{number}`: {}", ParseError::Empty);
+            return false;
+        }
+        Err(err) => {
+            eprintln!("Failed to parse `{number}`: {err}");
+            return false;
+        }
+    };
+    let trajectory = describe_trajectory(&digits, base);
+    for (idx, digits) in trajectory.numbers.iter().enumerate() {
+        println!("{idx}: {}", digits_string(digits));
+    }
+    println!("trajectory length: {}", trajectory.trajectory_len());
+    if let [fixed_point] = trajectory.cycle() {
+        println!("fixed point: {}", digits_string(fixed_point));
+    } else {
+        println!("cycle length: {}", trajectory.cycle_len());
+    }
+    true
+}
+
+/// Print the cycles of the describe map for each pair count, or every
+/// concrete cycle if `expand` is set
+fn print_cycles(
+    pair_counts: &PairCounts,
+    base: u8,
+    max_cycle_len: usize,
+    expand: bool,
+) {
+    for n_pairs in &pair_counts.0 {
+        println!("{n_pairs} PAIRS:");
+        for cycle in solve_cycles(*n_pairs, base, max_cycle_len) {
+            if expand {
+                for numbers in cycle.expand() {
+                    let numbers: Vec<String> =
+                        numbers.iter().map(|n| digits_string(n)).collect();
+                    println!("{}", numbers.join(" -> "));
                 }
+            } else {
+                print!("{cycle}");
             }
         }
     }
 }
 
+/// Print the autobiographical numbers of each length
+fn print_autobiographical(lengths: &PairCounts, base: u8) {
+    for n_digits in &lengths.0 {
+        println!("{n_digits} DIGITS:");
+        for digits in solve_autobiographical(*n_digits, base) {
+            println!("{}", digits_string(&digits));
+        }
+    }
+}
+
+/// Parse a pair count, a range of pair counts such as `1..=60` or `1..61`,
+/// or a comma-separated list of either, such as `5,10,37`
+fn parse_pair_counts(arg: &str) -> Result<PairCounts, String> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|err| format!("invalid pair count `{n}`: {err}"))
+    };
+    let mut res = Vec::new();
+    for item in arg.split(',') {
+        if let Some((start, end)) = item.split_once("..=") {
+            res.extend(parse(start)?..=parse(end)?);
+        } else if let Some((start, end)) = item.split_once("..") {
+            res.extend(parse(start)?..parse(end)?);
+        } else {
+            res.push(parse(item)?);
+        }
+    }
+    if res.is_empty() {
+        return Err(format!("`{arg}` contains no pair counts"));
+    }
+    Ok(PairCounts(res))
+}
+
+#[derive(Clone)]
+struct PairCounts(Vec<usize>);
+
+/// Parse a shard such as `2/8`, for the second of eight shards
+fn parse_shard(arg: &str) -> Result<Shard, String> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|err| format!("invalid shard `{arg}`: {err}"))
+    };
+    let Some((index, n_shards)) = arg.split_once('/') else {
+        return Err(format!("invalid shard `{arg}`: expected `i/N`"));
+    };
+    let index = parse(index)?;
+    let n_shards = parse(n_shards)?;
+    if index == 0 || index > n_shards {
+        return Err(format!(
+            "invalid shard `{arg}`: shard must be from 1 to {n_shards}"
+        ));
+    }
+    Ok(Shard {
+        index: index - 1,
+        n_shards,
+    })
+}
+
+/// Parse a number of unique digits, or a range such as `4..=6` or `4..7`
+fn parse_unique_digits(arg: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |n: &str| {
+        n.trim().parse::<usize>().map_err(|err| {
+            format!("invalid number of unique digits `{n}`: {err}")
+        })
+    };
+    if let Some((start, end)) = arg.split_once("..=") {
+        Ok(parse(start)?..=parse(end)?)
+    } else if let Some((start, end)) = arg.split_once("..") {
+        let end = parse(end)?;
+        if end == 0 {
+            return Err(format!(
+                "`{arg}` contains no numbers of unique digits"
+            ));
+        }
+        Ok(parse(start)?..=end - 1)
+    } else {
+        let n_unique_digits = parse(arg)?;
+        Ok(n_unique_digits..=n_unique_digits)
+    }
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    /// Number of pairs, a range such as `1..=60`, or a comma-separated list
+    /// such as `5,10,37`
+    #[arg(value_parser = parse_pair_counts, required_unless_present = "resume")]
+    pairs: Option<PairCounts>,
+    /// Numeral base, from 2 to 36
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+    base: u8,
+    /// Only search for solutions with this number of unique digits, or a
+    /// number of unique digits in a range such as `4..=6`
+    #[arg(long, value_parser = parse_unique_digits)]
+    unique_digits: Option<RangeInclusive<usize>>,
+    /// Print every concrete number, one per line
+    #[arg(long, conflicts_with = "format")]
+    expand: bool,
+    /// Order of the pairs of each concrete number: `count-desc`,
+    /// `count-asc`, `digit-desc` or `digit-asc`
+    #[arg(long, default_value_t = PairOrder::CountDesc, requires = "expand")]
+    order: PairOrder,
+    /// Print the number of distinct orderings of the pairs of each
+    /// concrete number after it
+    #[arg(long, requires = "expand")]
+    count_orderings: bool,
+    /// How each pair is written: `count-digit` or `digit-count`
+    #[arg(long, default_value_t = Convention::CountDigit)]
+    convention: Convention,
+    /// Print the number of solutions and concrete numbers for each number
+    /// of unique digits, instead of the solutions
+    #[arg(long, conflicts_with = "expand")]
+    count: bool,
+    /// Print one row for each pair count with the total number of solutions
+    /// and concrete numbers
+    #[arg(long, conflicts_with_all = ["expand", "count"])]
+    summary: bool,
+    /// Allow counts of `base` or more, written with several digits
+    #[arg(long)]
+    multi_digit_counts: bool,
+    /// Write counts in this base, from 2 up to the base of the numbers,
+    /// with as many digits as they need
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u8).range(2..=36),
+        conflicts_with = "multi_digit_counts"
+    )]
+    count_base: Option<u8>,
+    /// Spell counts as English words, and search for sentences that count
+    /// their own letters, in base 26
+    #[arg(long, conflicts_with_all = ["base", "multi_digit_counts", "count_base"])]
+    word_counts: bool,
+    /// Print only the numerically smallest concrete number for each pair
+    /// count
+    #[arg(long, conflicts_with_all = [
+        "expand", "count", "summary", "threads", "shard", "progress",
+        "stats", "trace", "checkpoint", "multi_digit_counts", "count_base",
+        "word_counts",
+    ])]
+    min: bool,
+    /// Print only the numerically largest concrete number for each pair
+    /// count
+    #[arg(long, conflicts_with_all = [
+        "min", "expand", "count", "summary", "threads", "shard", "progress",
+        "stats", "trace", "checkpoint", "multi_digit_counts", "count_base",
+        "word_counts",
+    ])]
+    max: bool,
+    /// Output format for solutions or counts
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Number of threads to search with, or 0 to use all available cores
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    /// Only search the `i`'th of `N` disjoint slices of the search, such as
+    /// `2/8`. Outputs written with `--format ndjson` can be combined with
+    /// the `merge` subcommand.
+    #[arg(long, value_parser = parse_shard)]
+    shard: Option<Shard>,
+    /// Report progress and the estimated time remaining on stderr
+    #[arg(long, conflicts_with = "threads")]
+    progress: bool,
+    /// Print the number of candidates rejected by each filter on stderr
+    #[arg(long, conflicts_with = "threads")]
+    stats: bool,
+    /// Print each rejected pairing of reps and descriptors on stderr, with
+    /// the filter that rejected it
+    #[arg(long, conflicts_with = "threads")]
+    trace: bool,
+    /// Periodically save the search position to this file, and only print
+    /// output up to the last saved position
+    #[arg(long, conflicts_with_all = ["count", "summary", "threads"])]
+    checkpoint: Option<PathBuf>,
+    /// Seconds between checkpoints
+    #[arg(long, default_value_t = 60)]
+    checkpoint_interval: u64,
+    /// Continue the search saved in a checkpoint file, without repeating
+    /// any output. Checkpoints continue to be saved to the same file,
+    /// unless `--checkpoint` is given.
+    #[arg(long, conflicts_with_all = [
+        "pairs", "base", "unique_digits", "expand", "count", "summary",
+        "format", "threads", "shard", "min", "max", "order",
+        "count_orderings", "multi_digit_counts", "count_base", "word_counts",
+        "convention",
+    ])]
+    resume: Option<PathBuf>,
+}
+
+impl SolveArgs {
+    fn counts(&self) -> Counts {
+        if self.word_counts {
+            Counts::Words
+        } else if let Some(count_base) = self.count_base {
+            Counts::Base(count_base)
+        } else if self.multi_digit_counts {
+            Counts::MultiDigit
+        } else {
+            Counts::SingleDigit
+        }
+    }
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    /// Number of pairs of the sharded searches, a range such as
+    /// `1..=60`, or a comma-separated list such as `5,10,37`
+    #[arg(value_parser = parse_pair_counts)]
+    pairs: PairCounts,
+    /// Output of each shard
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Numeral base, from 2 to 36
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+    base: u8,
+    /// Number of unique digits of the sharded searches
+    #[arg(long, value_parser = parse_unique_digits)]
+    unique_digits: Option<RangeInclusive<usize>>,
+    /// Print every concrete number, one per line
+    #[arg(long, conflicts_with = "format")]
+    expand: bool,
+    /// Order of the pairs of each concrete number: `count-desc`,
+    /// `count-asc`, `digit-desc` or `digit-asc`
+    #[arg(long, default_value_t = PairOrder::CountDesc, requires = "expand")]
+    order: PairOrder,
+    /// Print the number of distinct orderings of the pairs of each
+    /// concrete number after it
+    #[arg(long, requires = "expand")]
+    count_orderings: bool,
+    /// How each pair is written: `count-digit` or `digit-count`
+    #[arg(long, default_value_t = Convention::CountDigit)]
+    convention: Convention,
+    /// Output format for solutions
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check whether a number is self-descriptive
+    Verify {
+        number: String,
+        /// Numeral base, from 2 to 36
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+        base: u8,
+        /// How each pair is written: `count-digit` or `digit-count`
+        #[arg(long, default_value_t = Convention::CountDigit)]
+        convention: Convention,
+        /// Check whether the digit at each position is the number of times
+        /// the position's index occurs, instead of checking pairs
+        #[arg(long, conflicts_with = "convention")]
+        autobiographical: bool,
+    },
+    /// Repeatedly describe a number until it reaches a fixed point or a
+    /// cycle
+    Describe {
+        number: String,
+        /// Numeral base, from 2 to 36
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+        base: u8,
+    },
+    /// Search for cycles of numbers that describe each other, including
+    /// self-descriptive numbers as cycles of length 1
+    Cycles {
+        /// Number of pairs of each number, a range such as `1..=20`, or a
+        /// comma-separated list such as `5,10,12`
+        #[arg(value_parser = parse_pair_counts)]
+        pairs: PairCounts,
+        /// Numeral base, from 2 to 36
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+        base: u8,
+        /// Largest number of numbers in a cycle
+        #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u64).range(1..))]
+        max_len: u64,
+        /// Print every concrete cycle, one per line
+        #[arg(long)]
+        expand: bool,
+    },
+    /// Search for autobiographical numbers, such as `6210001000`, where the
+    /// digit at each position is the number of times the position's index
+    /// occurs
+    Autobiographical {
+        /// Number of digits, a range such as `1..=10`, or a comma-separated
+        /// list such as `4,5,7`
+        #[arg(value_parser = parse_pair_counts)]
+        lengths: PairCounts,
+        /// Numeral base, from 2 to 36
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+        base: u8,
+    },
+    /// Write the constraints on self-descriptive numbers with a number of
+    /// pairs, for an external SAT or ILP solver
+    Export {
+        /// Number of pairs
+        pairs: usize,
+        /// Numeral base, from 2 to 36
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(2..=36))]
+        base: u8,
+        /// Output format for the constraints
+        #[arg(long, value_enum, default_value_t = ExportFormat::Dimacs)]
+        format: ExportFormat,
+    },
+    /// Combine the outputs of sharded searches, written with
+    /// `--format ndjson`, in the same order as a single search
+    Merge(MergeArgs),
+}
+
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    pairs: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Present unless a subcommand is given
+    #[command(flatten)]
+    solve: Option<SolveArgs>,
+}
+
+type Solutions = Box<dyn Iterator<Item = Solution>>;
+
+#[cfg(feature = "serde")]
+fn solve_checkpointed(args: SolveArgs, reporting: Reporting) {
+    let counts = args.counts();
+    let interval = Duration::from_secs(args.checkpoint_interval);
+    let (run, path) = match (args.resume, args.checkpoint) {
+        (Some(resume), checkpoint) => {
+            (Run::load(&resume), checkpoint.unwrap_or(resume))
+        }
+        (None, Some(checkpoint)) => {
+            let PairCounts(pair_counts) = args.pairs.unwrap();
+            let printer = Printer::new(
+                args.base,
+                args.unique_digits.unwrap_or(1..=usize::MAX),
+                args.format,
+                args.expand.then_some(args.order),
+                args.count_orderings,
+                args.convention,
+                pair_counts.len() > 1,
+            );
+            let run = Run {
+                pair_counts,
+                shard: args.shard.unwrap_or_default(),
+                counts,
+                pairs_idx: 0,
+                printer,
+                search: None,
+            };
+            (run, checkpoint)
+        }
+        (None, None) => unreachable!(),
+    };
+    run.print_solutions(&path, interval, reporting)
+}
+
+#[cfg(not(feature = "serde"))]
+fn solve_checkpointed(_args: SolveArgs, _reporting: Reporting) {
+    eprintln!("Checkpoints require the `serde` feature");
+    std::process::exit(1)
+}
+
+#[cfg(feature = "serde")]
+fn merge(args: MergeArgs) {
+    let PairCounts(pair_counts) = args.pairs;
+    let mut printer = Printer::new(
+        args.base,
+        args.unique_digits.unwrap_or(1..=usize::MAX),
+        args.format,
+        args.expand.then_some(args.order),
+        args.count_orderings,
+        args.convention,
+        pair_counts.len() > 1,
+    );
+    let shards = args.files.into_iter().map(ShardOutput::open).collect();
+    print_merged(&pair_counts, &mut printer, shards)
+}
+
+#[cfg(not(feature = "serde"))]
+fn merge(_args: MergeArgs) {
+    eprintln!("Merging requires the `serde` feature");
+    std::process::exit(1)
+}
+
+fn solve(mut args: SolveArgs) {
+    if args.word_counts {
+        args.base = 26;
+    }
+    if let Some(count_base) = args.count_base
+        && count_base > args.base
+    {
+        eprintln!(
+            "Counts in base {count_base} cannot be written in base {}",
+            args.base
+        );
+        std::process::exit(1)
+    }
+    let reporting = Reporting {
+        progress: args.progress,
+        stats: args.stats,
+        trace: args.trace,
+    };
+    let digit_counts = args.counts();
+    if let Some(PairCounts(pair_counts)) = &args.pairs {
+        for n_pairs in pair_counts {
+            if !digit_counts.supports(*n_pairs, args.base) {
+                eprintln!(
+                    "Counts of {n_pairs} pairs may exceed {}, which is not \
+                     supported",
+                    u8::MAX
+                );
+                std::process::exit(1)
+            }
+        }
+    }
+    if args.checkpoint.is_some() || args.resume.is_some() {
+        return solve_checkpointed(args, reporting);
+    }
+    let SolveArgs {
+        pairs: Some(PairCounts(pair_counts)),
+        base,
+        threads,
+        format,
+        ..
+    } = args
+    else {
+        unreachable!()
+    };
+    let unique_digits = args.unique_digits.unwrap_or(1..=usize::MAX);
+    if args.min || args.max {
+        let extreme = if args.min { Extreme::Min } else { Extreme::Max };
+        let records = pair_counts.iter().map(|n_pairs| ExtremeRecord {
+            n_pairs: *n_pairs,
+            number: extreme_number(
+                *n_pairs,
+                base,
+                unique_digits.clone(),
+                extreme,
+                args.convention,
+            )
+            .map(|digits| digits_string(&digits)),
+        });
+        return print_extremes(records, format);
+    }
+    let shard = args.shard.unwrap_or_default();
+    let solutions = |n_pairs| -> Solutions {
+        if threads == 1 {
+            let solutions = solve_shard_iter(
+                n_pairs,
+                base,
+                unique_digits.clone(),
+                shard,
+                digit_counts,
+            );
+            Box::new(Search::new(
+                n_pairs,
+                base,
+                unique_digits.clone(),
+                digit_counts,
+                solutions,
+                reporting,
+            ))
+        } else {
+            match solve_shard_par_iter(
+                n_pairs,
+                base,
+                unique_digits.clone(),
+                shard,
+                digit_counts,
+                threads,
+            ) {
+                Ok(solutions) => Box::new(solutions),
+                Err(err) => {
+                    eprintln!("Failed to build thread pool: {err}");
+                    std::process::exit(1)
+                }
+            }
+        }
+    };
+    let counts = pair_counts.iter().map(|n_pairs| {
+        count_solutions(
+            *n_pairs,
+            base,
+            unique_digits.clone(),
+            solutions(*n_pairs),
+        )
+    });
+    if args.summary {
+        let summaries = counts.map(|counts| PairsCount::new(&counts));
+        print_summaries(summaries, format)
+    } else if args.count {
+        print_counts(counts, format, pair_counts.len() > 1)
+    } else {
+        let mut printer = Printer::new(
+            base,
+            unique_digits.clone(),
+            format,
+            args.expand.then_some(args.order),
+            args.count_orderings,
+            args.convention,
+            pair_counts.len() > 1,
+        );
+        print_solutions(&pair_counts, &mut printer, solutions)
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
-    solve(cli.pairs);
+    match cli.command {
+        Some(Command::Verify {
+            number,
+            base,
+            convention,
+            autobiographical,
+        }) => {
+            let is_self_descriptive = if autobiographical {
+                print_autobiographical_verification(&number, base)
+            } else {
+                print_verification(&number, base, convention)
+            };
+            if !is_self_descriptive {
+                std::process::exit(1)
+            }
+        }
+        Some(Command::Describe { number, base }) => {
+            if !print_trajectory(&number, base) {
+                std::process::exit(1)
+            }
+        }
+        Some(Command::Cycles {
+            pairs,
+            base,
+            max_len,
+            expand,
+        }) => print_cycles(&pairs, base, max_len as usize, expand),
+        Some(Command::Autobiographical { lengths, base }) => {
+            print_autobiographical(&lengths, base)
+        }
+        Some(Command::Export {
+            pairs,
+            base,
+            format,
+        }) => match format {
+            ExportFormat::Dimacs => {
+                print!("{}", self_descriptive_cnf(pairs, base))
+            }
+            ExportFormat::Lp => {
+                print!("{}", self_descriptive_model(pairs, base).lp())
+            }
+            ExportFormat::Mps => {
+                print!("{}", self_descriptive_model(pairs, base).mps())
+            }
+        },
+        Some(Command::Merge(args)) => merge(args),
+        None => solve(cli.solve.unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser as _;
+
+    use super::Cli;
+
+    #[test]
+    fn test_json_formats() {
+        for format in ["json", "ndjson"] {
+            let args = ["self-describing-numbers", "3", "--format", format];
+            assert_eq!(
+                Cli::try_parse_from(args).is_ok(),
+                cfg!(feature = "serde")
+            );
+        }
+    }
 }
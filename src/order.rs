@@ -0,0 +1,238 @@
+use crate::{Convention, Counts, Solution};
+
+/// Order of the (count, digit) pairs in a rendered number.
+/// A self-descriptive number remains self-descriptive when its pairs are
+/// reordered, so each order is a convention for choosing one of them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PairOrder {
+    /// Counts in descending order, then digits in descending order, as
+    /// produced by [`Solution::expand`]
+    #[default]
+    CountDesc,
+    /// Counts in ascending order, then digits in ascending order
+    CountAsc,
+    /// Digits in descending order
+    DigitDesc,
+    /// Digits in ascending order
+    DigitAsc,
+}
+
+impl PairOrder {
+    pub const ALL: [Self; 4] = [
+        Self::CountDesc,
+        Self::CountAsc,
+        Self::DigitDesc,
+        Self::DigitAsc,
+    ];
+
+    /// Sort (count, digit) pairs in this order
+    pub fn sort(self, pairs: &mut [(u8, u8)]) {
+        match self {
+            Self::CountDesc => {
+                pairs.sort_by_key(|pair| std::cmp::Reverse(*pair))
+            }
+            Self::CountAsc => pairs.sort(),
+            Self::DigitDesc => pairs.sort_by_key(|(count, digit)| {
+                std::cmp::Reverse((*digit, *count))
+            }),
+            Self::DigitAsc => {
+                pairs.sort_by_key(|(count, digit)| (*digit, *count))
+            }
+        }
+    }
+
+    /// Render the (count, digit) pairs of a number in this order, with
+    /// counts written as `counts` in base `base` and pairs written in
+    /// `convention`, such as the pairs of [`crate::Expansions::pairs`]
+    pub fn apply(
+        self,
+        pairs: &[(u8, u8)],
+        base: u8,
+        counts: Counts,
+        convention: Convention,
+    ) -> Vec<u8> {
+        let mut pairs = pairs.to_vec();
+        self.sort(&mut pairs);
+        let mut res = Vec::with_capacity(pairs.len() * 2);
+        for (count, digit) in pairs {
+            convention.write_pair(counts.digits(count, base), digit, &mut res);
+        }
+        res
+    }
+}
+
+impl std::fmt::Display for PairOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CountDesc => "count-desc",
+            Self::CountAsc => "count-asc",
+            Self::DigitDesc => "digit-desc",
+            Self::DigitAsc => "digit-asc",
+        }
+        .fmt(f)
+    }
+}
+
+impl std::str::FromStr for PairOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|order| order.to_string() == s)
+            .ok_or_else(|| {
+                let orders: Vec<_> =
+                    Self::ALL.iter().map(ToString::to_string).collect();
+                format!("expected one of {}", orders.join(", "))
+            })
+    }
+}
+
+/// Number of distinct orderings of the pairs of any concrete number
+/// represented by a solution, or `None` if it does not fit in a `u128`.
+/// A digit described by `reps` identical pairs contributes `reps!`
+/// indistinguishable orderings, so this is the multinomial coefficient of
+/// the number of pairs over the reps of each digit.
+pub fn count_orderings(solution: &Solution) -> Option<u128> {
+    let mut n_pairs = 0u128;
+    let mut res = 1u128;
+    for (reps, _, _) in &solution.best_solution {
+        // Multiply by the binomial coefficient C(n_pairs + reps, reps),
+        // one factor at a time so that each quotient is exact
+        for _ in 0..*reps {
+            n_pairs += 1;
+            res = res.checked_mul(n_pairs)?;
+        }
+        for idx in 1..=*reps as u128 {
+            res /= idx;
+        }
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        Convention, Counts, Shard, solve_iter, solve_shard_iter, verify_digits,
+    };
+
+    use super::{PairOrder, count_orderings};
+
+    #[test]
+    fn test_pair_order() {
+        for convention in Convention::ALL {
+            let apply = |order: PairOrder, pairs: &[(u8, u8)]| {
+                order.apply(pairs, 10, Counts::SingleDigit, convention)
+            };
+            for solution in solve_iter(8, 10, ..) {
+                let expansions = solution
+                    .expand()
+                    .with_convention(convention)
+                    .zip(solution.expand().pairs());
+                for (digits, pairs) in expansions {
+                    assert_eq!(apply(PairOrder::CountDesc, &pairs), digits);
+                    let mut digit_asc = pairs.clone();
+                    PairOrder::DigitAsc.sort(&mut digit_asc);
+                    for order in PairOrder::ALL {
+                        let ordered = apply(order, &pairs);
+                        assert!(
+                            verify_digits(&ordered, convention)
+                                .unwrap()
+                                .is_self_descriptive()
+                        );
+                        assert_eq!(apply(order, &digit_asc), ordered);
+                    }
+                }
+            }
+        }
+        let pairs = [(3, 1), (2, 2), (1, 3)];
+        let apply = |order: PairOrder, convention| {
+            order.apply(&pairs, 10, Counts::SingleDigit, convention)
+        };
+        let count_digit = Convention::CountDigit;
+        assert_eq!(apply(PairOrder::CountAsc, count_digit), [1, 3, 2, 2, 3, 1]);
+        assert_eq!(apply(PairOrder::DigitAsc, count_digit), [3, 1, 2, 2, 1, 3]);
+        assert_eq!(
+            apply(PairOrder::DigitDesc, count_digit),
+            [1, 3, 2, 2, 3, 1]
+        );
+        let digit_count = Convention::DigitCount;
+        assert_eq!(apply(PairOrder::CountAsc, digit_count), [3, 1, 2, 2, 1, 3]);
+        assert_eq!(apply(PairOrder::DigitAsc, digit_count), [1, 3, 2, 2, 3, 1]);
+        for order in PairOrder::ALL {
+            assert_eq!(order.to_string().parse(), Ok(order));
+        }
+    }
+
+    #[test]
+    fn test_pair_order_multi_digit_counts() {
+        for counts in [Counts::MultiDigit, Counts::Base(2)] {
+            let solutions =
+                solve_shard_iter(5, 3, .., Shard::default(), counts);
+            for solution in solutions {
+                for convention in Convention::ALL {
+                    let expansions = solution
+                        .expand()
+                        .with_convention(convention)
+                        .zip(solution.expand().pairs());
+                    for (digits, pairs) in expansions {
+                        let apply = |order: PairOrder, pairs: &[(u8, u8)]| {
+                            order.apply(pairs, 3, counts, convention)
+                        };
+                        assert_eq!(apply(PairOrder::CountDesc, &pairs), digits);
+                        // Expanded pairs are in descending order, so they
+                        // are written in reverse for the ascending order
+                        let mut count_asc = Vec::new();
+                        for (count, digit) in pairs.iter().rev() {
+                            convention.write_pair(
+                                counts.digits(*count, 3),
+                                *digit,
+                                &mut count_asc,
+                            );
+                        }
+                        assert_eq!(
+                            apply(PairOrder::CountAsc, &pairs),
+                            count_asc
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_orderings() {
+        for n_pairs in 1..=6 {
+            for solution in solve_iter(n_pairs, 10, ..) {
+                let digits = solution.expand().next().unwrap();
+                let pairs: Vec<_> = digits.chunks_exact(2).collect();
+                let orderings: BTreeSet<Vec<_>> =
+                    permutations(&pairs).into_iter().collect();
+                assert_eq!(
+                    count_orderings(&solution),
+                    Some(orderings.len() as u128)
+                );
+            }
+        }
+    }
+
+    fn permutations<T: Clone>(xs: &[T]) -> Vec<Vec<T>> {
+        if xs.is_empty() {
+            return vec![Vec::new()];
+        }
+        let mut res = Vec::new();
+        for idx in 0..xs.len() {
+            let mut rest = xs.to_vec();
+            let x = rest.remove(idx);
+            for mut permutation in permutations(&rest) {
+                permutation.push(x.clone());
+                res.push(permutation);
+            }
+        }
+        res
+    }
+}
@@ -1,9 +1,11 @@
 use arrayvec::ArrayVec;
 use lender::{Lend, Lender, Lending};
 
+use crate::MAX_BASE;
+
 struct Inner {
-    acc: ArrayVec<u8, 10>,
-    ys: ArrayVec<u8, 9>,
+    acc: ArrayVec<u8, MAX_BASE>,
+    ys: ArrayVec<u8, { MAX_BASE - 1 }>,
 }
 
 /// Iterate over all possible sorted pairings in lexicographic order
@@ -12,7 +14,7 @@ pub struct OrderedPairings {
     /// Buffer used to store results.
     /// The first element of the pairs are xs. The second elements are
     /// the ys of the previous iterator value, or zeros upon initialization.
-    res_buf: ArrayVec<(u8, u8), 10>,
+    res_buf: ArrayVec<(u8, u8), MAX_BASE>,
 }
 
 impl OrderedPairings {
@@ -45,6 +47,29 @@ impl OrderedPairings {
         let res_buf = xs.iter().map(|x| (*x, 0)).collect();
         Self { inner, res_buf }
     }
+
+    /// Stack of partial pairings that remain to be explored, as pairs of
+    /// the ys paired so far and the remaining ys
+    pub fn stack(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.inner
+            .iter()
+            .map(|inner| (inner.acc.to_vec(), inner.ys.to_vec()))
+            .collect()
+    }
+
+    /// Continue iterating from a stack obtained from [`Self::stack`], with
+    /// the same xs
+    pub fn resume(xs: &[u8], stack: &[(Vec<u8>, Vec<u8>)]) -> Self {
+        let inner = stack
+            .iter()
+            .map(|(acc, ys)| Inner {
+                acc: acc.iter().copied().collect(),
+                ys: ys.iter().copied().collect(),
+            })
+            .collect();
+        let res_buf = xs.iter().map(|x| (*x, 0)).collect();
+        Self { inner, res_buf }
+    }
 }
 
 impl<'lend> Lending<'lend> for OrderedPairings {
@@ -135,4 +160,23 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_ordered_pairings_resume() {
+        let xs = [1, 2, 2, 3];
+        let ys = [4, 5, 5, 6];
+        let mut all = Vec::new();
+        OrderedPairings::new(&xs, &ys)
+            .for_each(|pairing| all.push(pairing.to_owned()));
+        for n_skip in 0..=all.len() {
+            let mut iter = OrderedPairings::new(&xs, &ys);
+            for _ in 0..n_skip {
+                iter.next();
+            }
+            let mut resumed = Vec::new();
+            OrderedPairings::resume(&xs, &iter.stack())
+                .for_each(|pairing| resumed.push(pairing.to_owned()));
+            assert_eq!(resumed, all[n_skip..]);
+        }
+    }
 }
@@ -0,0 +1,172 @@
+//! Search for solutions on a thread pool.
+
+use std::ops::RangeBounds;
+
+use rayon::{
+    ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder,
+    iter::{IntoParallelRefIterator, ParallelIterator},
+};
+
+use crate::{Counts, Pipeline, Shard, Solution, WorkItem, shard_work_items};
+
+/// Number of work items to solve per thread before yielding results
+const WORK_ITEMS_PER_THREAD: usize = 64;
+
+/// Iterator over solutions that solves batches of work items on a thread
+/// pool.
+/// Solutions are yielded in the same order as the serial search.
+pub struct ParSolveIter<I> {
+    pool: ThreadPool,
+    work_items: I,
+    base: u8,
+    counts: Counts,
+    pipeline: Pipeline,
+    batch_size: usize,
+    solutions: std::vec::IntoIter<Solution>,
+}
+
+impl<I> ParSolveIter<I>
+where
+    I: Iterator<Item = WorkItem>,
+{
+    /// Solve `work_items` in base `base`, with counts written as `counts`,
+    /// using `n_threads` threads.
+    /// If `n_threads` is 0, the number of threads is chosen automatically.
+    pub fn new(
+        work_items: I,
+        base: u8,
+        counts: Counts,
+        n_threads: usize,
+    ) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new().num_threads(n_threads).build()?;
+        let batch_size = pool.current_num_threads() * WORK_ITEMS_PER_THREAD;
+        Ok(Self {
+            pool,
+            work_items,
+            base,
+            counts,
+            pipeline: Pipeline::default(),
+            batch_size,
+            solutions: Vec::new().into_iter(),
+        })
+    }
+
+    /// Apply the filters of `pipeline` instead of the built-in filters
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+}
+
+impl<I> Iterator for ParSolveIter<I>
+where
+    I: Iterator<Item = WorkItem>,
+{
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(solution) = self.solutions.next() {
+                return Some(solution);
+            }
+            let batch: Vec<WorkItem> =
+                self.work_items.by_ref().take(self.batch_size).collect();
+            if batch.is_empty() {
+                return None;
+            }
+            let base = self.base;
+            let counts = self.counts;
+            let pipeline = &self.pipeline;
+            let solutions: Vec<Solution> = self.pool.install(|| {
+                batch
+                    .par_iter()
+                    .flat_map_iter(|work_item| {
+                        work_item.solve_pipeline(base, counts, pipeline)
+                    })
+                    .collect()
+            });
+            self.solutions = solutions.into_iter();
+        }
+    }
+}
+
+/// Iterate over solutions in base `base` with `n_pairs` pairs and a number
+/// of unique digits in `unique_digits`, using `n_threads` threads.
+/// Solutions are yielded in the same order as [`crate::solve_iter`].
+pub fn solve_par_iter(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    n_threads: usize,
+) -> Result<ParSolveIter<impl Iterator<Item = WorkItem>>, ThreadPoolBuildError>
+{
+    solve_shard_par_iter(
+        n_pairs,
+        base,
+        unique_digits,
+        Shard::default(),
+        Counts::default(),
+        n_threads,
+    )
+}
+
+/// Iterate over the solutions in `shard`, in base `base` with `n_pairs`
+/// pairs and a number of unique digits in `unique_digits`, with counts
+/// written as `counts`, using `n_threads` threads.
+/// Solutions are yielded in the same order as [`crate::solve_shard_iter`].
+pub fn solve_shard_par_iter(
+    n_pairs: usize,
+    base: u8,
+    unique_digits: impl RangeBounds<usize>,
+    shard: Shard,
+    counts: Counts,
+    n_threads: usize,
+) -> Result<ParSolveIter<impl Iterator<Item = WorkItem>>, ThreadPoolBuildError>
+{
+    let work_items =
+        shard_work_items(n_pairs, base, unique_digits, shard, counts);
+    ParSolveIter::new(work_items, base, counts, n_threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Counts, Shard, solve_iter, solve_shard_iter};
+
+    use super::{solve_par_iter, solve_shard_par_iter};
+
+    #[test]
+    fn test_par_solve_order() {
+        for n_pairs in [5, 12, 15] {
+            let serial: Vec<String> = solve_iter(n_pairs, 10, ..)
+                .map(|solution| solution.to_string())
+                .collect();
+            let parallel: Vec<String> = solve_par_iter(n_pairs, 10, .., 4)
+                .unwrap()
+                .map(|solution| solution.to_string())
+                .collect();
+            assert_eq!(serial, parallel);
+        }
+    }
+
+    #[test]
+    fn test_par_solve_shard_order() {
+        let shard = Shard {
+            index: 2,
+            n_shards: 3,
+        };
+        for (n_pairs, base, counts) in
+            [(12, 10, Counts::SingleDigit), (6, 4, Counts::MultiDigit)]
+        {
+            let serial: Vec<String> =
+                solve_shard_iter(n_pairs, base, .., shard, counts)
+                    .map(|solution| solution.to_string())
+                    .collect();
+            let parallel: Vec<String> =
+                solve_shard_par_iter(n_pairs, base, .., shard, counts, 4)
+                    .unwrap()
+                    .map(|solution| solution.to_string())
+                    .collect();
+            assert_eq!(serial, parallel);
+        }
+    }
+}
@@ -1,12 +1,75 @@
-/// Iterator over partitions of an integer with `PARTS` parts
-#[repr(transparent)]
+/// Table of the number of partitions with bounded parts, for every number of
+/// parts, integer and maximum part up to `n_parts`, `n` and `max_part`
+struct PartitionCounts {
+    n_parts: usize,
+    n: usize,
+    /// Indexed by max part, then number of parts, then integer
+    table: Vec<u128>,
+}
+
+impl PartitionCounts {
+    fn new(n_parts: usize, n: usize, max_part: u8) -> Self {
+        let mut res = Self {
+            n_parts,
+            n,
+            table: vec![0; (max_part as usize + 1) * (n_parts + 1) * (n + 1)],
+        };
+        for max_part in 0..=max_part as usize {
+            for n_parts in 0..=res.n_parts {
+                for n in 0..=res.n {
+                    let count = if n_parts == 0 {
+                        (n == 0) as u128
+                    } else if max_part == 0 || n < n_parts {
+                        0
+                    } else {
+                        // Partitions with a part equal to 1, and partitions
+                        // with every part greater than 1
+                        res.table[res.idx(max_part, n_parts - 1, n - 1)]
+                            + res.table
+                                [res.idx(max_part - 1, n_parts, n - n_parts)]
+                    };
+                    let idx = res.idx(max_part, n_parts, n);
+                    res.table[idx] = count;
+                }
+            }
+        }
+        res
+    }
+
+    fn idx(&self, max_part: usize, n_parts: usize, n: usize) -> usize {
+        (max_part * (self.n_parts + 1) + n_parts) * (self.n + 1) + n
+    }
+
+    /// Number of partitions of `n` into `n_parts` parts, where every part is
+    /// at least `min_part` and at most `max_part`
+    fn get(
+        &self,
+        n_parts: usize,
+        n: usize,
+        min_part: u8,
+        max_part: u8,
+    ) -> u128 {
+        if min_part > max_part {
+            return (n_parts == 0 && n == 0) as u128;
+        }
+        // Subtract `min_part - 1` from every part
+        let Some(n) = n.checked_sub(n_parts * (min_part as usize - 1)) else {
+            return 0;
+        };
+        self.table[self.idx((max_part - min_part + 1) as usize, n_parts, n)]
+    }
+}
+
+/// Iterator over partitions of an integer with `PARTS` parts, where no part
+/// is greater than `max_part`
 pub struct PartitionsParts {
     inner: Option<Vec<u8>>,
+    max_part: u8,
 }
 
 impl PartitionsParts {
-    pub fn new(n_parts: usize, n: usize) -> Self {
-        let inner = if n < n_parts || n_parts * 9 < n {
+    pub fn new(n_parts: usize, n: usize, max_part: u8) -> Self {
+        let inner = if n < n_parts || n_parts * (max_part as usize) < n {
             None
         } else if n_parts == 0 {
             Some(Vec::new())
@@ -14,11 +77,12 @@ impl PartitionsParts {
             let mut parts = vec![1u8; n_parts];
             let mut r_idx = n_parts - 1;
             let mut sum = n_parts;
+            let max_incr = max_part as usize - 1;
             loop {
                 let rem = n - sum;
-                if rem >= 8 {
-                    parts[r_idx] = 9;
-                    sum += 8;
+                if rem >= max_incr {
+                    parts[r_idx] = max_part;
+                    sum += max_incr;
                     if r_idx == 0 {
                         assert_eq!(sum, n);
                         break;
@@ -32,7 +96,79 @@ impl PartitionsParts {
             }
             Some(parts)
         };
-        Self { inner }
+        Self { inner, max_part }
+    }
+
+    /// Number of partitions yielded by [`Self::new`]
+    pub fn count(n_parts: usize, n: usize, max_part: u8) -> u128 {
+        PartitionCounts::new(n_parts, n, max_part).get(n_parts, n, 1, max_part)
+    }
+
+    /// Index of `parts` in the partitions yielded by [`Self::new`], for the
+    /// same number of parts and sum.
+    /// `parts` must be sorted, with no part greater than `max_part`.
+    pub fn rank(parts: &[u8], max_part: u8) -> u128 {
+        let n_parts = parts.len();
+        let n = parts.iter().copied().map(usize::from).sum();
+        let counts = PartitionCounts::new(n_parts, n, max_part);
+        let mut res = 0;
+        let mut min_part = 1;
+        let mut remaining = n;
+        for (idx, part) in parts.iter().copied().enumerate() {
+            // Partitions with a smaller part at this index come first
+            for smaller in min_part..part {
+                if let Some(rest) = remaining.checked_sub(smaller as usize) {
+                    res +=
+                        counts.get(n_parts - idx - 1, rest, smaller, max_part);
+                }
+            }
+            min_part = part;
+            remaining -= part as usize;
+        }
+        res
+    }
+
+    /// The partition at `index` in the partitions yielded by [`Self::new`],
+    /// or `None` if there are not enough partitions
+    pub fn unrank(
+        n_parts: usize,
+        n: usize,
+        max_part: u8,
+        mut index: u128,
+    ) -> Option<Vec<u8>> {
+        let counts = PartitionCounts::new(n_parts, n, max_part);
+        if index >= counts.get(n_parts, n, 1, max_part) {
+            return None;
+        }
+        let mut res = Vec::with_capacity(n_parts);
+        let mut min_part = 1;
+        let mut remaining = n;
+        for idx in 0..n_parts {
+            // Skip the partitions with a smaller part at this index
+            let part = (min_part..=max_part).find(|part| {
+                let count =
+                    remaining.checked_sub(*part as usize).map_or(0, |rest| {
+                        counts.get(n_parts - idx - 1, rest, *part, max_part)
+                    });
+                if index < count {
+                    return true;
+                }
+                index -= count;
+                false
+            })?;
+            res.push(part);
+            min_part = part;
+            remaining -= part as usize;
+        }
+        Some(res)
+    }
+
+    /// Continue iterating from `parts`, which is the next partition yielded
+    pub fn resume(parts: Vec<u8>, max_part: u8) -> Self {
+        Self {
+            inner: Some(parts),
+            max_part,
+        }
     }
 }
 
@@ -50,7 +186,7 @@ impl Iterator for PartitionsParts {
         let mut l_idx = r_idx - 1;
         loop {
             let l_val = next[l_idx];
-            if l_val == 9 {
+            if l_val == self.max_part {
                 if l_idx == 0 {
                     break;
                 } else {
@@ -77,7 +213,8 @@ impl Iterator for PartitionsParts {
                 *value = new_l_val;
             }
             while r_sum_remaining > 0 {
-                let incr = (9 - new_l_val as usize).min(r_sum_remaining);
+                let incr = (self.max_part as usize - new_l_val as usize)
+                    .min(r_sum_remaining);
                 r_sum_remaining -= incr;
                 next[r_idx] += incr as u8;
                 r_idx -= 1;
@@ -95,93 +232,93 @@ mod tests {
 
     #[test]
     fn test_partitions_1_part() {
-        let iter_0 = PartitionsParts::new(1, 0);
+        let iter_0 = PartitionsParts::new(1, 0, 9);
         assert!(iter_0.collect::<Vec<_>>().is_empty());
-        let iter_1 = PartitionsParts::new(1, 1);
+        let iter_1 = PartitionsParts::new(1, 1, 9);
         assert_eq!(iter_1.collect::<Vec<_>>(), [[1u8]]);
-        let iter_2 = PartitionsParts::new(1, 2);
+        let iter_2 = PartitionsParts::new(1, 2, 9);
         assert_eq!(iter_2.collect::<Vec<_>>(), [[2u8]]);
-        let iter_9 = PartitionsParts::new(1, 9);
+        let iter_9 = PartitionsParts::new(1, 9, 9);
         assert_eq!(iter_9.collect::<Vec<_>>(), [[9u8]]);
-        let iter_10 = PartitionsParts::new(1, 10);
+        let iter_10 = PartitionsParts::new(1, 10, 9);
         assert!(iter_10.collect::<Vec<_>>().is_empty());
     }
 
     #[test]
     fn test_partitions_2_parts() {
-        let iter_0 = PartitionsParts::new(2, 0);
+        let iter_0 = PartitionsParts::new(2, 0, 9);
         assert!(iter_0.collect::<Vec<_>>().is_empty());
-        let iter_1 = PartitionsParts::new(2, 1);
+        let iter_1 = PartitionsParts::new(2, 1, 9);
         assert!(iter_1.collect::<Vec<_>>().is_empty());
-        let iter_2 = PartitionsParts::new(2, 2);
+        let iter_2 = PartitionsParts::new(2, 2, 9);
         assert_eq!(iter_2.collect::<Vec<_>>(), [[1, 1]]);
-        let iter_3 = PartitionsParts::new(2, 3);
+        let iter_3 = PartitionsParts::new(2, 3, 9);
         assert_eq!(iter_3.collect::<Vec<_>>(), [[1, 2]]);
-        let iter_4 = PartitionsParts::new(2, 4);
+        let iter_4 = PartitionsParts::new(2, 4, 9);
         assert_eq!(iter_4.collect::<Vec<_>>(), [[1, 3], [2, 2]]);
-        let iter_5 = PartitionsParts::new(2, 5);
+        let iter_5 = PartitionsParts::new(2, 5, 9);
         assert_eq!(iter_5.collect::<Vec<_>>(), [[1, 4], [2, 3]]);
-        let iter_9 = PartitionsParts::new(2, 9);
+        let iter_9 = PartitionsParts::new(2, 9, 9);
         assert_eq!(
             iter_9.collect::<Vec<_>>(),
             [[1, 8], [2, 7], [3, 6], [4, 5]]
         );
-        let iter_10 = PartitionsParts::new(2, 10);
+        let iter_10 = PartitionsParts::new(2, 10, 9);
         assert_eq!(
             iter_10.collect::<Vec<_>>(),
             [[1, 9], [2, 8], [3, 7], [4, 6], [5, 5]]
         );
-        let iter_11 = PartitionsParts::new(2, 11);
+        let iter_11 = PartitionsParts::new(2, 11, 9);
         assert_eq!(
             iter_11.collect::<Vec<_>>(),
             [[2, 9], [3, 8], [4, 7], [5, 6]]
         );
-        let iter_16 = PartitionsParts::new(2, 16);
+        let iter_16 = PartitionsParts::new(2, 16, 9);
         assert_eq!(iter_16.collect::<Vec<_>>(), [[7, 9], [8, 8]]);
-        let iter_17 = PartitionsParts::new(2, 17);
+        let iter_17 = PartitionsParts::new(2, 17, 9);
         assert_eq!(iter_17.collect::<Vec<_>>(), [[8, 9]]);
-        let iter_18 = PartitionsParts::new(2, 18);
+        let iter_18 = PartitionsParts::new(2, 18, 9);
         assert_eq!(iter_18.collect::<Vec<_>>(), [[9, 9]]);
-        let iter_19 = PartitionsParts::new(2, 19);
+        let iter_19 = PartitionsParts::new(2, 19, 9);
         assert!(iter_19.collect::<Vec<_>>().is_empty());
     }
 
     #[test]
     fn test_partitions_3_parts() {
-        let iter_0 = PartitionsParts::new(3, 0);
+        let iter_0 = PartitionsParts::new(3, 0, 9);
         assert!(iter_0.collect::<Vec<_>>().is_empty());
-        let iter_1 = PartitionsParts::new(3, 1);
+        let iter_1 = PartitionsParts::new(3, 1, 9);
         assert!(iter_1.collect::<Vec<_>>().is_empty());
-        let iter_2 = PartitionsParts::new(3, 2);
+        let iter_2 = PartitionsParts::new(3, 2, 9);
         assert!(iter_2.collect::<Vec<_>>().is_empty());
-        let iter_3 = PartitionsParts::new(3, 3);
+        let iter_3 = PartitionsParts::new(3, 3, 9);
         assert_eq!(iter_3.collect::<Vec<_>>(), [[1, 1, 1]]);
-        let iter_5 = PartitionsParts::new(3, 5);
+        let iter_5 = PartitionsParts::new(3, 5, 9);
         assert_eq!(iter_5.collect::<Vec<_>>(), [[1, 1, 3], [1, 2, 2]]);
-        let iter_7 = PartitionsParts::new(3, 7);
+        let iter_7 = PartitionsParts::new(3, 7, 9);
         assert_eq!(
             iter_7.collect::<Vec<_>>(),
             [[1, 1, 5], [1, 2, 4], [1, 3, 3], [2, 2, 3]]
         );
-        let iter_25 = PartitionsParts::new(3, 25);
+        let iter_25 = PartitionsParts::new(3, 25, 9);
         assert_eq!(iter_25.collect::<Vec<_>>(), [[7, 9, 9], [8, 8, 9]]);
-        let iter_26 = PartitionsParts::new(3, 26);
+        let iter_26 = PartitionsParts::new(3, 26, 9);
         assert_eq!(iter_26.collect::<Vec<_>>(), [[8, 9, 9]]);
-        let iter_27 = PartitionsParts::new(3, 27);
+        let iter_27 = PartitionsParts::new(3, 27, 9);
         assert_eq!(iter_27.collect::<Vec<_>>(), [[9, 9, 9]]);
-        let iter_28 = PartitionsParts::new(3, 28);
+        let iter_28 = PartitionsParts::new(3, 28, 9);
         assert!(iter_28.collect::<Vec<_>>().is_empty());
     }
 
     #[test]
     fn test_partitions_4_parts() {
         for n in 0..4 {
-            let iter = PartitionsParts::new(4, n);
+            let iter = PartitionsParts::new(4, n, 9);
             assert!(iter.collect::<Vec<_>>().is_empty());
         }
-        let iter_4 = PartitionsParts::new(4, 4);
+        let iter_4 = PartitionsParts::new(4, 4, 9);
         assert_eq!(iter_4.collect::<Vec<_>>(), [[1, 1, 1, 1]]);
-        let iter_8 = PartitionsParts::new(4, 8);
+        let iter_8 = PartitionsParts::new(4, 8, 9);
         assert_eq!(
             iter_8.collect::<Vec<_>>(),
             [
@@ -192,7 +329,7 @@ mod tests {
                 [2, 2, 2, 2]
             ]
         );
-        let iter_10 = PartitionsParts::new(4, 10);
+        let iter_10 = PartitionsParts::new(4, 10, 9);
         assert_eq!(
             iter_10.collect::<Vec<_>>(),
             [
@@ -207,7 +344,7 @@ mod tests {
                 [2, 2, 3, 3],
             ]
         );
-        let iter_12 = PartitionsParts::new(4, 12);
+        let iter_12 = PartitionsParts::new(4, 12, 9);
         assert_eq!(
             iter_12.collect::<Vec<_>>(),
             [
@@ -228,7 +365,7 @@ mod tests {
                 [3, 3, 3, 3]
             ]
         );
-        let iter_16 = PartitionsParts::new(4, 16);
+        let iter_16 = PartitionsParts::new(4, 16, 9);
         assert_eq!(
             iter_16.collect::<Vec<_>>(),
             [
@@ -261,7 +398,7 @@ mod tests {
                 [4, 4, 4, 4]
             ]
         );
-        let iter_20 = PartitionsParts::new(4, 20);
+        let iter_20 = PartitionsParts::new(4, 20, 9);
         assert_eq!(
             iter_20.collect::<Vec<_>>(),
             [
@@ -300,7 +437,7 @@ mod tests {
                 [5, 5, 5, 5],
             ]
         );
-        let iter_32 = PartitionsParts::new(4, 32);
+        let iter_32 = PartitionsParts::new(4, 32, 9);
         assert_eq!(
             iter_32.collect::<Vec<_>>(),
             [
@@ -311,9 +448,73 @@ mod tests {
                 [8, 8, 8, 8]
             ]
         );
-        let iter_36 = PartitionsParts::new(4, 36);
+        let iter_36 = PartitionsParts::new(4, 36, 9);
         assert_eq!(iter_36.collect::<Vec<_>>(), [[9, 9, 9, 9]]);
-        let iter_37 = PartitionsParts::new(4, 37);
+        let iter_37 = PartitionsParts::new(4, 37, 9);
         assert!(iter_37.collect::<Vec<_>>().is_empty());
     }
+
+    #[test]
+    fn test_partitions_max_part() {
+        let iter_3 = PartitionsParts::new(3, 7, 3);
+        assert_eq!(iter_3.collect::<Vec<_>>(), [[1, 3, 3], [2, 2, 3]]);
+        let iter_1 = PartitionsParts::new(3, 3, 1);
+        assert_eq!(iter_1.collect::<Vec<_>>(), [[1, 1, 1]]);
+        let iter_1 = PartitionsParts::new(3, 4, 1);
+        assert!(iter_1.collect::<Vec<_>>().is_empty());
+        let iter_35 = PartitionsParts::new(2, 60, 35);
+        assert_eq!(
+            iter_35.collect::<Vec<_>>(),
+            [[25, 35], [26, 34], [27, 33], [28, 32], [29, 31], [30, 30]]
+        );
+    }
+
+    #[test]
+    fn test_partitions_resume() {
+        let all: Vec<_> = PartitionsParts::new(4, 16, 9).collect();
+        for (idx, parts) in all.iter().enumerate() {
+            let resumed: Vec<_> =
+                PartitionsParts::resume(parts.clone(), 9).collect();
+            assert_eq!(resumed, all[idx..]);
+        }
+    }
+
+    #[test]
+    fn test_partitions_rank_unrank() {
+        for (n_parts, max_part) in [(0, 9), (1, 9), (3, 3), (4, 9), (2, 35)] {
+            for n in 0..=n_parts * max_part as usize + 1 {
+                let all: Vec<_> =
+                    PartitionsParts::new(n_parts, n, max_part).collect();
+                assert_eq!(
+                    PartitionsParts::count(n_parts, n, max_part),
+                    all.len() as u128
+                );
+                for (idx, parts) in all.iter().enumerate() {
+                    assert_eq!(
+                        PartitionsParts::rank(parts, max_part),
+                        idx as u128
+                    );
+                    assert_eq!(
+                        PartitionsParts::unrank(
+                            n_parts,
+                            n,
+                            max_part,
+                            idx as u128
+                        )
+                        .as_ref(),
+                        Some(parts)
+                    );
+                }
+                assert_eq!(
+                    PartitionsParts::unrank(
+                        n_parts,
+                        n,
+                        max_part,
+                        all.len() as u128
+                    ),
+                    None
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,569 @@
+//! Serial search that can be checkpointed and resumed.
+
+use std::{
+    ops::{RangeBounds, RangeInclusive},
+    task::Poll,
+};
+
+use lender::Lender;
+
+use crate::{
+    Counts, Filter, OrderedPairings, PartitionsParts, Pipeline, Shard,
+    Solution, Stats, unique_digits_range,
+};
+
+/// Position of a search within the reps and descriptors for a number of
+/// unique digits
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Position {
+    /// Index of the next work item
+    pub work_item_idx: usize,
+    pub n_unique_digits: usize,
+    pub reps: Vec<u8>,
+    pub descriptors: Vec<u8>,
+    /// Partial pairings of reps and descriptors that remain to be explored,
+    /// from [`OrderedPairings::stack`]
+    pub pairings: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// State of a search, from which it can be resumed
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Checkpoint {
+    pub n_pairs: usize,
+    pub base: u8,
+    pub unique_digits: RangeInclusive<usize>,
+    pub shard: Shard,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub counts: Counts,
+    /// `None` if the search is finished
+    pub position: Option<Position>,
+}
+
+/// Position of a search within the search space, for reporting progress
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Progress {
+    pub n_unique_digits: usize,
+    /// Index of the current reps, among the reps for this number of unique
+    /// digits
+    pub reps_idx: u128,
+    pub n_reps: u128,
+    /// Index of the current descriptors, among the descriptors for this
+    /// number of unique digits and number of digits
+    pub descriptors_idx: u128,
+    pub n_descriptors: u128,
+    /// Index of the current work item, including work items in other shards
+    pub work_item_idx: u128,
+    /// Number of pairings examined by this iterator
+    pub n_pairings: u64,
+}
+
+/// Callback for pairings rejected by a filter
+type Trace = dyn FnMut(&[(u8, u8)], &dyn Filter);
+
+/// Iterator over solutions in base `base` with `n_pairs` pairs, in order of
+/// the number of unique digits.
+/// The state of the iterator can be saved with [`Self::checkpoint`], and
+/// restored with [`Self::resume`].
+pub struct SolveIter {
+    n_pairs: usize,
+    base: u8,
+    unique_digits: RangeInclusive<usize>,
+    shard: Shard,
+    counts: Counts,
+    /// Index of the next work item, including work items in other shards
+    work_item_idx: usize,
+    n_unique_digits: usize,
+    /// Sum of the descriptors yielded by `descriptors_iter`
+    n_digits: usize,
+    /// `None` if the search is finished
+    reps_iter: Option<PartitionsParts>,
+    reps: Vec<u8>,
+    /// `None` until the first reps for a number of unique digits
+    descriptors_iter: Option<PartitionsParts>,
+    descriptors: Vec<u8>,
+    /// `None` if the search is finished
+    pairings: Option<OrderedPairings>,
+    pipeline: Pipeline,
+    stats: Stats,
+    /// Called with each pairing that is rejected, and the filter that
+    /// rejected it
+    trace: Option<Box<Trace>>,
+}
+
+impl SolveIter {
+    pub fn new(
+        n_pairs: usize,
+        base: u8,
+        unique_digits: impl RangeBounds<usize>,
+    ) -> Self {
+        Self::new_shard(
+            n_pairs,
+            base,
+            unique_digits,
+            Shard::default(),
+            Counts::default(),
+        )
+    }
+
+    /// Only search the work items in `shard`, with counts written as
+    /// `counts`
+    pub fn new_shard(
+        n_pairs: usize,
+        base: u8,
+        unique_digits: impl RangeBounds<usize>,
+        shard: Shard,
+        counts: Counts,
+    ) -> Self {
+        let unique_digits = unique_digits_range(n_pairs, base, unique_digits);
+        let n_unique_digits = *unique_digits.start();
+        let max_part = counts.max_count(n_pairs, base);
+        let reps_iter = (!unique_digits.is_empty())
+            .then(|| PartitionsParts::new(n_unique_digits, n_pairs, max_part));
+        let mut res = Self {
+            n_pairs,
+            base,
+            unique_digits,
+            shard,
+            counts,
+            work_item_idx: 0,
+            n_unique_digits,
+            n_digits: *counts.n_digits_range(n_pairs, base).start(),
+            reps_iter,
+            reps: Vec::new(),
+            descriptors_iter: None,
+            descriptors: Vec::new(),
+            pairings: None,
+            pipeline: Pipeline::default(),
+            stats: Stats::default(),
+            trace: None,
+        };
+        res.advance();
+        res
+    }
+
+    /// Move on to the next reps and descriptors that pass the filters,
+    /// or finish the search if there are none
+    fn advance(&mut self) {
+        self.pairings = None;
+        let max_part = self.counts.max_count(self.n_pairs, self.base);
+        let max_n_digits = self.counts.max_n_digits(self.n_pairs, self.base);
+        while let Some(reps_iter) = &mut self.reps_iter {
+            if let Some(descriptors) = self
+                .descriptors_iter
+                .as_mut()
+                .and_then(|descriptors_iter| descriptors_iter.next())
+            {
+                let work_item_idx = self.work_item_idx;
+                self.work_item_idx += 1;
+                if !self.shard.contains(work_item_idx) {
+                    continue;
+                }
+                self.stats.n_work_items += 1;
+                match self.pipeline.filter_reps_descriptors(
+                    &self.reps,
+                    &descriptors,
+                    self.base,
+                    self.counts,
+                ) {
+                    Ok(()) => {
+                        self.pairings = Some(OrderedPairings::new(
+                            &self.reps,
+                            &descriptors,
+                        ));
+                        self.descriptors = descriptors;
+                        return;
+                    }
+                    Err(filter_idx) => self.stats.reject(filter_idx),
+                }
+            } else if self.descriptors_iter.is_some()
+                && self.n_digits < max_n_digits
+            {
+                self.n_digits += 1;
+                self.descriptors_iter = Some(PartitionsParts::new(
+                    self.n_unique_digits,
+                    self.n_digits,
+                    max_part,
+                ));
+            } else if let Some(reps) = reps_iter.next() {
+                self.reps = reps;
+                self.n_digits = *self
+                    .counts
+                    .n_digits_range(self.n_pairs, self.base)
+                    .start();
+                self.descriptors_iter = Some(PartitionsParts::new(
+                    self.n_unique_digits,
+                    self.n_digits,
+                    max_part,
+                ));
+            } else {
+                self.descriptors_iter = None;
+                self.n_unique_digits += 1;
+                self.reps_iter = (self.n_unique_digits
+                    <= *self.unique_digits.end())
+                .then(|| {
+                    PartitionsParts::new(
+                        self.n_unique_digits,
+                        self.n_pairs,
+                        max_part,
+                    )
+                });
+            }
+        }
+    }
+
+    /// Save the state of the search.
+    /// Resuming from the checkpoint yields the solutions that this iterator
+    /// has not yet yielded.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            n_pairs: self.n_pairs,
+            base: self.base,
+            unique_digits: self.unique_digits.clone(),
+            shard: self.shard,
+            counts: self.counts,
+            position: self.pairings.as_ref().map(|pairings| Position {
+                work_item_idx: self.work_item_idx,
+                n_unique_digits: self.n_unique_digits,
+                reps: self.reps.clone(),
+                descriptors: self.descriptors.clone(),
+                pairings: pairings.stack(),
+            }),
+        }
+    }
+
+    /// Resume a search from a checkpoint obtained from [`Self::checkpoint`]
+    pub fn resume(checkpoint: Checkpoint) -> Self {
+        let Checkpoint {
+            n_pairs,
+            base,
+            unique_digits,
+            shard,
+            counts,
+            position,
+        } = checkpoint;
+        let max_part = counts.max_count(n_pairs, base);
+        let Some(position) = position else {
+            return Self {
+                n_pairs,
+                base,
+                n_unique_digits: *unique_digits.end() + 1,
+                unique_digits,
+                shard,
+                counts,
+                work_item_idx: 0,
+                n_digits: *counts.n_digits_range(n_pairs, base).start(),
+                reps_iter: None,
+                reps: Vec::new(),
+                descriptors_iter: None,
+                descriptors: Vec::new(),
+                pairings: None,
+                pipeline: Pipeline::default(),
+                stats: Stats::default(),
+                trace: None,
+            };
+        };
+        // Resumed partition iterators yield the current partition first
+        let mut reps_iter =
+            PartitionsParts::resume(position.reps.clone(), max_part);
+        reps_iter.next();
+        let mut descriptors_iter =
+            PartitionsParts::resume(position.descriptors.clone(), max_part);
+        descriptors_iter.next();
+        let pairings =
+            OrderedPairings::resume(&position.reps, &position.pairings);
+        Self {
+            n_pairs,
+            base,
+            unique_digits,
+            shard,
+            counts,
+            work_item_idx: position.work_item_idx,
+            n_unique_digits: position.n_unique_digits,
+            n_digits: position.descriptors.iter().map(|d| *d as usize).sum(),
+            reps_iter: Some(reps_iter),
+            reps: position.reps,
+            descriptors_iter: Some(descriptors_iter),
+            descriptors: position.descriptors,
+            pairings: Some(pairings),
+            pipeline: Pipeline::default(),
+            stats: Stats::default(),
+            trace: None,
+        }
+    }
+
+    /// Call `trace` with each pairing that is rejected, and the filter that
+    /// rejected it
+    pub fn with_trace(
+        mut self,
+        trace: impl FnMut(&[(u8, u8)], &dyn Filter) + 'static,
+    ) -> Self {
+        self.trace = Some(Box::new(trace));
+        self
+    }
+
+    /// Apply the filters of `pipeline` instead of the built-in filters.
+    /// Checkpoints do not record the pipeline, so it is set again after
+    /// resuming.
+    /// Should be called before iterating, since only the current reps and
+    /// descriptors are checked again.
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = pipeline;
+        // The current reps and descriptors passed the previous pipeline
+        if self.pairings.is_some()
+            && let Err(filter_idx) = self.pipeline.filter_reps_descriptors(
+                &self.reps,
+                &self.descriptors,
+                self.base,
+                self.counts,
+            )
+        {
+            self.stats.reject(filter_idx);
+            self.advance();
+        }
+        self
+    }
+
+    /// Filters applied by this iterator
+    pub fn pipeline(&self) -> &Pipeline {
+        &self.pipeline
+    }
+
+    /// Number of candidates examined and rejected by this iterator
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Position of the search, or `None` if the search is finished
+    pub fn progress(&self) -> Option<Progress> {
+        self.pairings.as_ref()?;
+        let max_part = self.counts.max_count(self.n_pairs, self.base);
+        Some(Progress {
+            n_unique_digits: self.n_unique_digits,
+            reps_idx: PartitionsParts::rank(&self.reps, max_part),
+            n_reps: PartitionsParts::count(
+                self.n_unique_digits,
+                self.n_pairs,
+                max_part,
+            ),
+            descriptors_idx: PartitionsParts::rank(&self.descriptors, max_part),
+            n_descriptors: PartitionsParts::count(
+                self.n_unique_digits,
+                self.n_digits,
+                max_part,
+            ),
+            // Only the next work item is tracked
+            work_item_idx: self.work_item_idx as u128 - 1,
+            n_pairings: self.stats.n_pairings,
+        })
+    }
+
+    /// Examine at most `max_pairings` pairings, stopping at the next
+    /// solution.
+    /// Returns [`Poll::Pending`] if neither a solution nor the end of the
+    /// search was reached, so that long searches can do other work, such
+    /// as reporting progress, between solutions.
+    pub fn poll_next(&mut self, max_pairings: u64) -> Poll<Option<Solution>> {
+        let mut n_pairings = 0;
+        loop {
+            let Some(pairings) = self.pairings.as_mut() else {
+                return Poll::Ready(None);
+            };
+            loop {
+                if n_pairings == max_pairings {
+                    return Poll::Pending;
+                }
+                let Some(rep_descriptors) = pairings.next() else {
+                    break;
+                };
+                n_pairings += 1;
+                self.stats.n_pairings += 1;
+                match self.pipeline.filter_pairing(
+                    rep_descriptors,
+                    self.base,
+                    self.counts,
+                ) {
+                    Ok(solution) => {
+                        self.stats.n_solutions += 1;
+                        return Poll::Ready(Some(solution));
+                    }
+                    Err(filter_idx) => {
+                        self.stats.reject(filter_idx);
+                        if let Some(trace) = &mut self.trace {
+                            trace(
+                                rep_descriptors,
+                                self.pipeline.get(filter_idx),
+                            );
+                        }
+                    }
+                }
+            }
+            self.advance();
+        }
+    }
+}
+
+impl Iterator for SolveIter {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Poll::Ready(solution) = self.poll_next(u64::MAX) {
+                return solution;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Poll;
+
+    use crate::{Counts, Shard, Solution, count_work_items, work_items};
+
+    use super::SolveIter;
+
+    #[test]
+    fn test_resume() {
+        for (n_pairs, unique_digits) in [(8, 1..=8), (12, 2..=4)] {
+            let all: Vec<String> =
+                SolveIter::new(n_pairs, 10, unique_digits.clone())
+                    .map(|solution| solution.to_string())
+                    .collect();
+            assert!(!all.is_empty());
+            let mut iter = SolveIter::new(n_pairs, 10, unique_digits);
+            for idx in 0..=all.len() {
+                let resumed: Vec<String> = SolveIter::resume(iter.checkpoint())
+                    .map(|solution| solution.to_string())
+                    .collect();
+                assert_eq!(resumed, all[idx..]);
+                iter.next();
+            }
+            assert_eq!(iter.checkpoint().position, None);
+        }
+    }
+
+    #[test]
+    fn test_resume_shard() {
+        let shard = Shard {
+            index: 1,
+            n_shards: 3,
+        };
+        let all: Vec<String> =
+            SolveIter::new_shard(12, 10, 2..=4, shard, Counts::SingleDigit)
+                .map(|solution| solution.to_string())
+                .collect();
+        assert!(!all.is_empty());
+        let mut iter =
+            SolveIter::new_shard(12, 10, 2..=4, shard, Counts::SingleDigit);
+        for idx in 0..=all.len() {
+            let resumed: Vec<String> = SolveIter::resume(iter.checkpoint())
+                .map(|solution| solution.to_string())
+                .collect();
+            assert_eq!(resumed, all[idx..]);
+            iter.next();
+        }
+    }
+
+    #[test]
+    fn test_resume_multi_digit_counts() {
+        let new = || {
+            SolveIter::new_shard(5, 3, .., Shard::default(), Counts::MultiDigit)
+        };
+        let all: Vec<String> =
+            new().map(|solution| solution.to_string()).collect();
+        assert!(!all.is_empty());
+        let mut iter = new();
+        for idx in 0..=all.len() {
+            let resumed: Vec<String> = SolveIter::resume(iter.checkpoint())
+                .map(|solution| solution.to_string())
+                .collect();
+            assert_eq!(resumed, all[idx..]);
+            iter.next();
+        }
+    }
+
+    #[test]
+    fn test_shards() {
+        let all: Vec<Solution> = SolveIter::new(12, 10, ..).collect();
+        assert!(
+            all.windows(2)
+                .all(|pair| pair[0].work_item() <= pair[1].work_item())
+        );
+        let all: Vec<String> =
+            all.iter().map(|solution| solution.to_string()).collect();
+        for n_shards in [2, 3, 7] {
+            let mut merged: Vec<Solution> = (0..n_shards)
+                .flat_map(|index| {
+                    let shard = Shard { index, n_shards };
+                    SolveIter::new_shard(12, 10, .., shard, Counts::SingleDigit)
+                })
+                .collect();
+            merged.sort_by_key(|solution| solution.work_item());
+            let merged: Vec<String> =
+                merged.iter().map(|solution| solution.to_string()).collect();
+            assert_eq!(merged, all);
+        }
+    }
+
+    #[test]
+    fn test_poll_next() {
+        let all: Vec<String> = SolveIter::new(12, 10, ..)
+            .map(|solution| solution.to_string())
+            .collect();
+        let mut iter = SolveIter::new(12, 10, ..);
+        let mut polled = Vec::new();
+        let mut n_pending = 0;
+        loop {
+            match iter.poll_next(100) {
+                Poll::Ready(Some(solution)) => {
+                    polled.push(solution.to_string())
+                }
+                Poll::Ready(None) => break,
+                Poll::Pending => n_pending += 1,
+            }
+            if let Some(progress) = iter.progress() {
+                assert!(progress.reps_idx < progress.n_reps);
+                assert!(progress.descriptors_idx < progress.n_descriptors);
+            }
+        }
+        assert_eq!(polled, all);
+        assert!(n_pending > 0);
+        assert_eq!(iter.progress(), None);
+    }
+
+    #[test]
+    fn test_count_work_items() {
+        for (n_pairs, base, counts) in [
+            (1, 10, Counts::SingleDigit),
+            (12, 10, Counts::SingleDigit),
+            (20, 5, Counts::SingleDigit),
+            (7, 36, Counts::SingleDigit),
+            (8, 10, Counts::MultiDigit),
+            (10, 3, Counts::MultiDigit),
+            (4, 3, Counts::Base(2)),
+            (3, 26, Counts::Words),
+        ] {
+            let n_work_items = count_work_items(n_pairs, base, .., counts);
+            assert_eq!(
+                n_work_items,
+                work_items(n_pairs, base, .., counts).count() as u128
+            );
+            // The search visits the same work items
+            let mut iter = SolveIter::new_shard(
+                n_pairs,
+                base,
+                ..,
+                Shard::default(),
+                counts,
+            );
+            iter.by_ref().for_each(drop);
+            assert_eq!(iter.stats().n_work_items as u128, n_work_items);
+        }
+        assert_eq!(
+            count_work_items(12, 10, 3..=4, Counts::SingleDigit),
+            work_items(12, 10, 3..=4, Counts::SingleDigit).count() as u128
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Statistics on how much of the search each filter prunes.
+
+use crate::{Filter, Pipeline, Stage};
+
+/// A built-in filter, which may reject a candidate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rejection {
+    /// Reps are lexicographically greater than descriptors
+    RepsGtDescriptors,
+    /// [`crate::check_free_vars`]
+    FreeVars,
+    /// [`crate::check_reps_lte_descriptor`]
+    RepsLteDescriptor,
+    /// [`crate::check_free_vars_rep_descriptor`]
+    FreeVarsRepDescriptor,
+    /// [`crate::check_reps_descriptor_counts`]
+    RepsDescriptorCounts,
+}
+
+impl Rejection {
+    /// Every built-in filter, in the order they are applied
+    pub const ALL: [Self; 5] = [
+        Self::RepsGtDescriptors,
+        Self::FreeVars,
+        Self::RepsLteDescriptor,
+        Self::FreeVarsRepDescriptor,
+        Self::RepsDescriptorCounts,
+    ];
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/// Number of candidates examined and rejected at each stage of a search
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Stats {
+    /// Candidate reps and descriptors
+    pub n_work_items: u64,
+    /// Pairings of reps and descriptors that passed the filters on reps and
+    /// descriptors
+    pub n_pairings: u64,
+    /// Number of candidates rejected by each filter, by its index in the
+    /// [`Pipeline`] of the search, which for built-in filters is the index
+    /// of their [`Rejection`]
+    pub n_rejected: Vec<u64>,
+    pub n_solutions: u64,
+}
+
+impl Stats {
+    pub fn reject(&mut self, filter_idx: usize) {
+        if self.n_rejected.len() <= filter_idx {
+            self.n_rejected.resize(filter_idx + 1, 0);
+        }
+        self.n_rejected[filter_idx] += 1;
+    }
+
+    pub fn n_rejected(&self, filter_idx: usize) -> u64 {
+        self.n_rejected.get(filter_idx).copied().unwrap_or(0)
+    }
+
+    /// Number of candidates reaching each filter of `pipeline`, and the
+    /// number rejected by it, in the order the filters were added, which is
+    /// the order they are applied within each stage
+    pub fn funnel<'a>(
+        &'a self,
+        pipeline: &'a Pipeline,
+    ) -> impl Iterator<Item = (&'a dyn Filter, u64, u64)> {
+        let mut n_work_items = self.n_work_items;
+        let mut n_pairings = self.n_pairings;
+        pipeline
+            .filters()
+            .enumerate()
+            .map(move |(filter_idx, filter)| {
+                let n_candidates = match filter.stage() {
+                    Stage::RepsDescriptors => &mut n_work_items,
+                    Stage::Pairing => &mut n_pairings,
+                };
+                let n_in = *n_candidates;
+                let n_rejected = self.n_rejected(filter_idx);
+                *n_candidates -= n_rejected;
+                (filter, n_in, n_rejected)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{Counts, Filter, SolveIter, Stage, count_work_items};
+
+    use super::Rejection;
+
+    #[test]
+    fn test_stats_funnel() {
+        let n_traced = Rc::new(RefCell::new([0u64; Rejection::ALL.len()]));
+        let mut solutions = SolveIter::new(12, 10, ..).with_trace({
+            let n_traced = n_traced.clone();
+            move |_, filter| {
+                let filter_idx = Rejection::ALL
+                    .iter()
+                    .position(|rejection| rejection.name() == filter.name())
+                    .unwrap();
+                n_traced.borrow_mut()[filter_idx] += 1;
+            }
+        });
+        let n_solutions = solutions.by_ref().count() as u64;
+        let stats = solutions.stats();
+        assert_eq!(stats.n_solutions, n_solutions);
+        assert_eq!(
+            stats.n_work_items as u128,
+            count_work_items(12, 10, .., Counts::SingleDigit)
+        );
+        let (_, n_in, n_rejected) =
+            stats.funnel(solutions.pipeline()).last().unwrap();
+        assert_eq!(n_in - n_rejected, n_solutions);
+        for rejection in Rejection::ALL {
+            let n_traced = n_traced.borrow()[rejection as usize];
+            if rejection.stage() == Stage::Pairing {
+                assert_eq!(n_traced, stats.n_rejected(rejection as usize));
+            } else {
+                assert_eq!(n_traced, 0);
+            }
+        }
+    }
+}
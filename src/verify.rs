@@ -0,0 +1,246 @@
+use crate::{Convention, MAX_BASE, digit_char};
+
+/// Error parsing a number into (count, digit) pairs
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseError {
+    Empty,
+    /// A character that is not a digit in the base
+    InvalidDigit {
+        position: usize,
+        char: char,
+    },
+    /// The number of digits is odd, so the last pair is incomplete
+    OddLength {
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "number is empty"),
+            Self::InvalidDigit { position, char } => {
+                write!(f, "invalid digit `{char}` at position {position}")
+            }
+            Self::OddLength { len } => {
+                write!(f, "number has an odd number of digits ({len})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a number in base `base` into digits
+pub fn parse_digits(number: &str, base: u8) -> Result<Vec<u8>, ParseError> {
+    number
+        .chars()
+        .enumerate()
+        .map(|(position, char)| {
+            char.to_digit(base as u32)
+                .map(|digit| digit as u8)
+                .ok_or(ParseError::InvalidDigit { position, char })
+        })
+        .collect()
+}
+
+/// A (count, digit) pair whose count is wrong
+#[derive(Debug, Eq, PartialEq)]
+pub struct WrongPair {
+    /// Index of the pair in the number
+    pub idx: usize,
+    pub count: u8,
+    pub digit: u8,
+    /// Number of times the digit occurs in the number
+    pub actual_count: usize,
+    /// Convention the pair is written in
+    pub convention: Convention,
+}
+
+impl std::fmt::Display for WrongPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (first, second) = match self.convention {
+            Convention::CountDigit => (self.count, self.digit),
+            Convention::DigitCount => (self.digit, self.count),
+        };
+        write!(
+            f,
+            "pair {} ({}{}): {} occurs {} times, not {}",
+            self.idx,
+            digit_char(first),
+            digit_char(second),
+            digit_char(self.digit),
+            self.actual_count,
+            self.count,
+        )
+    }
+}
+
+/// Result of checking a number against a pair convention
+#[derive(Debug, Eq, PartialEq)]
+pub struct Verification {
+    /// (count, digit) pairs, in the order they occur in the number
+    pub pairs: Vec<(u8, u8)>,
+    /// Pairs whose count does not match the number of occurrences of the
+    /// digit
+    pub wrong_pairs: Vec<WrongPair>,
+    /// Digits that occur in the number without a pair describing them,
+    /// in ascending order
+    pub undescribed: Vec<u8>,
+}
+
+impl Verification {
+    pub fn is_self_descriptive(&self) -> bool {
+        self.wrong_pairs.is_empty() && self.undescribed.is_empty()
+    }
+}
+
+/// Check whether the digits form a self-descriptive number, where each
+/// pair, written in `convention`, gives the number of times its digit
+/// occurs.
+pub fn verify_digits(
+    digits: &[u8],
+    convention: Convention,
+) -> Result<Verification, ParseError> {
+    if digits.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(ParseError::OddLength { len: digits.len() });
+    }
+    let mut counts = [0usize; MAX_BASE];
+    for digit in digits {
+        counts[*digit as usize] += 1;
+    }
+    let pairs: Vec<(u8, u8)> = digits
+        .chunks(2)
+        .map(|pair| convention.pair(pair[0], pair[1]))
+        .collect();
+    let mut described = [false; MAX_BASE];
+    let mut wrong_pairs = Vec::new();
+    for (idx, (count, digit)) in pairs.iter().copied().enumerate() {
+        described[digit as usize] = true;
+        let actual_count = counts[digit as usize];
+        if count as usize != actual_count {
+            wrong_pairs.push(WrongPair {
+                idx,
+                count,
+                digit,
+                actual_count,
+                convention,
+            });
+        }
+    }
+    let undescribed = (0..MAX_BASE as u8)
+        .filter(|digit| {
+            counts[*digit as usize] != 0 && !described[*digit as usize]
+        })
+        .collect();
+    Ok(Verification {
+        pairs,
+        wrong_pairs,
+        undescribed,
+    })
+}
+
+/// Parse a number in base `base` and check whether it is self-descriptive,
+/// with pairs written in `convention`
+pub fn verify(
+    number: &str,
+    base: u8,
+    convention: Convention,
+) -> Result<Verification, ParseError> {
+    verify_digits(&parse_digits(number, base)?, convention)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Convention;
+
+    use super::{ParseError, WrongPair, verify};
+
+    #[test]
+    fn test_verify() {
+        assert!(
+            verify("999999969696686868686868", 10, Convention::CountDigit)
+                .unwrap()
+                .is_self_descriptive()
+        );
+        assert!(
+            verify("4442422929", 10, Convention::CountDigit)
+                .unwrap()
+                .is_self_descriptive()
+        );
+        assert!(
+            verify("22", 10, Convention::CountDigit)
+                .unwrap()
+                .is_self_descriptive()
+        );
+        let verification =
+            verify("4442422928", 10, Convention::CountDigit).unwrap();
+        assert_eq!(
+            verification.wrong_pairs,
+            [
+                WrongPair {
+                    idx: 3,
+                    count: 2,
+                    digit: 9,
+                    actual_count: 1,
+                    convention: Convention::CountDigit,
+                },
+                WrongPair {
+                    idx: 4,
+                    count: 2,
+                    digit: 8,
+                    actual_count: 1,
+                    convention: Convention::CountDigit,
+                },
+            ]
+        );
+        assert!(verification.undescribed.is_empty());
+        let verification = verify("2525", 10, Convention::CountDigit).unwrap();
+        assert!(verification.wrong_pairs.is_empty());
+        assert_eq!(verification.undescribed, [2]);
+        assert!(!verification.is_self_descriptive());
+    }
+
+    #[test]
+    fn test_verify_digit_count() {
+        let digit_count = Convention::DigitCount;
+        assert!(
+            verify("4424249292", 10, digit_count)
+                .unwrap()
+                .is_self_descriptive()
+        );
+        assert!(
+            !verify("4442422929", 10, digit_count)
+                .unwrap()
+                .is_self_descriptive()
+        );
+        let verification = verify("4424249282", 10, digit_count).unwrap();
+        assert_eq!(
+            verification.wrong_pairs[0].to_string(),
+            "pair 3 (92): 9 occurs 1 times, not 2"
+        );
+    }
+
+    #[test]
+    fn test_verify_parse_errors() {
+        assert_eq!(
+            verify("", 10, Convention::CountDigit),
+            Err(ParseError::Empty)
+        );
+        assert_eq!(
+            verify("221", 10, Convention::CountDigit),
+            Err(ParseError::OddLength { len: 3 })
+        );
+        assert_eq!(
+            verify("2A", 10, Convention::CountDigit),
+            Err(ParseError::InvalidDigit {
+                position: 1,
+                char: 'A'
+            })
+        );
+        assert!(verify("2A", 11, Convention::CountDigit).is_ok());
+    }
+}